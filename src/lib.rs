@@ -13,10 +13,14 @@ mod util;
 mod valid;
 
 use path::*;
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use util::{pmatch, tostr, unescape};
-pub use valid::valid;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use util::{exceeds_depth, fmt_usize, leading_f64, pmatch, sanitized_f64, tostr, trim, unescape};
+pub use valid::{valid, valid_no_dupes, valid_with_depth};
 
 type InfoBits = u32;
 
@@ -32,6 +36,21 @@ pub enum Kind {
     Object,
 }
 
+impl Kind {
+    /// Returns the lowercase name of the kind, such as "string" or "object".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Null => "null",
+            Kind::False => "false",
+            Kind::Number => "number",
+            Kind::String => "string",
+            Kind::True => "true",
+            Kind::Array => "array",
+            Kind::Object => "object",
+        }
+    }
+}
+
 impl PartialOrd for Kind {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -81,9 +100,18 @@ static KINDMAP: [Kind; 256] = {
 pub struct Value<'a> {
     slice: &'a str,
     owned: String,
-    uescstr: String,
+    uescstr: Cow<'a, str>,
     info: InfoBits,
     index: Option<usize>,
+    // The position of the value in its parent, when it was found by an array
+    // index or a query.
+    ordinal: Option<usize>,
+    // The number of elements in the parent array, when the value was found by
+    // `get_with_meta`.
+    siblings: Option<usize>,
+    // The raw key and value, when the value is an object member. This is
+    // borrowed along with the value, and owned along with an owned value.
+    member: Cow<'a, str>,
 }
 
 impl<'a> Eq for Value<'a> {}
@@ -123,14 +151,42 @@ impl<'a> Ord for Value<'a> {
     }
 }
 
+impl<'a> Hash for Value<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Values that are equal must hash the same, so this follows the
+        // same rules as cmp.
+        let kind = self.kind();
+        (kind as i32).hash(state);
+        if kind == Kind::String {
+            self.str().hash(state);
+        } else if kind == Kind::Number {
+            let x = self.f64();
+            let x = if x == 0.0 {
+                // -0 and 0 are equal
+                0.0
+            } else if x.is_nan() {
+                f64::NAN
+            } else {
+                x
+            };
+            x.to_bits().hash(state);
+        } else {
+            self.json().hash(state);
+        }
+    }
+}
+
 impl<'a> Default for Value<'a> {
     fn default() -> Self {
         return Value {
             slice: "",
             owned: String::default(),
-            uescstr: String::default(),
+            uescstr: Cow::Borrowed(""),
             info: 0,
             index: None,
+            ordinal: None,
+            siblings: None,
+            member: Cow::Borrowed(""),
         };
     }
 }
@@ -145,9 +201,12 @@ fn json_clone_from_ref<'a>(json: &'a Value<'a>) -> Value<'a> {
     Value {
         slice: json.json(),
         owned: String::new(),
-        uescstr: json.uescstr.to_owned(),
+        uescstr: Cow::Borrowed(&json.uescstr),
         info: json.info,
         index: json.index,
+        ordinal: json.ordinal,
+        siblings: json.siblings,
+        member: Cow::Borrowed(&json.member),
     }
 }
 
@@ -155,9 +214,12 @@ fn json_from_slice<'a>(slice: &'a [u8], index: Option<usize>, info: InfoBits) ->
     let mut json = Value {
         slice: tostr(slice),
         owned: String::new(),
-        uescstr: String::new(),
+        uescstr: Cow::Borrowed(""),
         info,
         index,
+        ordinal: None,
+        siblings: None,
+        member: Cow::Borrowed(""),
     };
     json_unescape_string(&mut json);
     return json;
@@ -167,9 +229,12 @@ fn json_from_owned<'a>(owned: String, index: Option<usize>, info: InfoBits) -> V
     let mut json = Value {
         slice: "",
         owned: owned,
-        uescstr: String::new(),
+        uescstr: Cow::Borrowed(""),
         info,
         index,
+        ordinal: None,
+        siblings: None,
+        member: Cow::Borrowed(""),
     };
     json_unescape_string(&mut json);
     return json;
@@ -178,12 +243,16 @@ fn json_from_owned<'a>(owned: String, index: Option<usize>, info: InfoBits) -> V
 fn json_unescape_string<'a>(json: &mut Value<'a>) {
     if json.info & (INFO_STRING | INFO_ESC) == (INFO_STRING | INFO_ESC) {
         // Escaped string. We must unescape it into a new allocated string.
-        json.uescstr = unescape(json.json());
+        json.uescstr = Cow::Owned(unescape(json.json()));
     }
 }
 
 impl<'a> Value<'a> {
     pub fn get(&'a self, path: &'a str) -> Value<'a> {
+        if path.is_empty() {
+            // An empty path is the value itself.
+            return json_clone_from_ref(self);
+        }
         let mut json = if self.slice.len() > 0 {
             get(&self.slice, path)
         } else {
@@ -199,6 +268,172 @@ impl<'a> Value<'a> {
         json
     }
 
+    /// Returns every value that matches the path, rather than only the first
+    /// match as `get` does. This is useful for paths that are ambiguous, such
+    /// as a wildcard key that matches many children or an object with
+    /// duplicate keys. The matches are in document order.
+    ///
+    /// Queries, modifiers, and multipaths are evaluated the same as `get`,
+    /// each contributing at most one value.
+    ///
+    /// ```
+    /// let value = gjson::parse(r#"{"a":1,"b":2,"a":3}"#);
+    /// let all = value.get_all("a");
+    /// assert_eq!(all.len(), 2);
+    /// ```
+    pub fn get_all(&'a self, path: &'a str) -> Vec<Value<'a>> {
+        let mut all = Vec::new();
+        if path.is_empty() {
+            if self.exists() {
+                all.push(json_clone_from_ref(self));
+            }
+        } else if self.exists() {
+            get_all_values(self.json(), self.index, path, &mut all);
+        }
+        all
+    }
+
+    /// Returns the array element at index, the same as `get` with the index
+    /// as the path, but without formatting the index into a path.
+    /// Returns a non-existent value when the value isn't an array.
+    pub fn get_index(&'a self, index: usize) -> Value<'a> {
+        if self.kind() != Kind::Array {
+            return Value::default();
+        }
+        let target = if index <= i64::MAX as usize { index as i64 } else { -1 };
+        let (mut res, _, _) = arr_child_at(self.json().as_bytes(), 0, Lines::Off, target, false);
+        res.index = match (self.index, res.index) {
+            (Some(index1), Some(index2)) => Some(index1 + index2),
+            _ => None,
+        };
+        res
+    }
+
+    /// Returns the value at path, or a `PathMiss` error when the value does
+    /// not exist.
+    pub fn try_get(&'a self, path: &'a str) -> Result<Value<'a>, PathMiss> {
+        let value = self.get(path);
+        if value.exists() {
+            Ok(value)
+        } else {
+            Err(PathMiss::new(path))
+        }
+    }
+
+    /// Returns the value at path, or the default json parsed as a value when
+    /// the path does not exist.
+    ///
+    /// ```
+    /// let value = gjson::parse(r#"{"name":"Tom"}"#);
+    /// assert_eq!(value.get_or("count", "0").i64(), 0);
+    /// assert_eq!(value.get_or("name", r#""anonymous""#).str(), "Tom");
+    /// ```
+    pub fn get_or(&'a self, path: &'a str, default_json: &'a str) -> Value<'a> {
+        let value = self.get(path);
+        if value.exists() {
+            value
+        } else {
+            parse(default_json)
+        }
+    }
+
+    /// Returns the value at path, or the default formatted as a json literal
+    /// when the path does not exist.
+    /// Unlike `get_or`, the default is a Rust value rather than json text, so
+    /// a string default is quoted and escaped as a json string.
+    ///
+    /// ```
+    /// let value = gjson::parse(r#"{"name":"Tom"}"#);
+    /// assert_eq!(value.get_or_literal("count", 0).i64(), 0);
+    /// assert_eq!(value.get_or_literal("nick", "anonymous").str(), "anonymous");
+    /// ```
+    pub fn get_or_literal(&'a self, path: &'a str, default: impl IntoJsonLiteral) -> Value<'a> {
+        let value = self.get(path);
+        if value.exists() {
+            value
+        } else {
+            json_into_owned(parse(&default.into_json_literal()))
+        }
+    }
+
+    /// Returns the value of the first path that exists, or a non-existent
+    /// value when none of the paths exist.
+    pub fn first_existing(&'a self, paths: &[&'a str]) -> Value<'a> {
+        for path in paths {
+            let value = self.get(path);
+            if value.exists() {
+                return value;
+            }
+        }
+        Value::default()
+    }
+
+    /// Returns true if every one of the paths exists, such as for checking
+    /// the required fields of a payload.
+    pub fn all_exist(&'a self, paths: &[&'a str]) -> bool {
+        paths.iter().all(|path| self.get(path).exists())
+    }
+
+    /// Returns true if any one of the paths exists.
+    pub fn any_exist(&'a self, paths: &[&'a str]) -> bool {
+        paths.iter().any(|path| self.get(path).exists())
+    }
+
+    /// Returns the value at path along with its byte offset in the original
+    /// json, when known.
+    ///
+    /// The offset and the length of the raw json can be used to splice the
+    /// original json, such as replacing a value in-place.
+    ///
+    /// ```
+    /// let json = r#"{"name":{"first":"Tom","last":"Anderson"}}"#;
+    /// let root = gjson::parse(json);
+    /// let (value, index) = root.get_indexed("name.last");
+    /// if let Some(index) = index {
+    ///     let end = index + value.json().len();
+    ///     let json = format!("{}{}{}", &json[..index], r#""Smith""#, &json[end..]);
+    ///     assert_eq!(json, r#"{"name":{"first":"Tom","last":"Smith"}}"#);
+    /// }
+    /// ```
+    ///
+    /// The offset is `None` for values that are not directly sliced from the
+    /// original json, such as those produced by modifiers or multipaths.
+    pub fn get_indexed(&'a self, path: &'a str) -> (Value<'a>, Option<usize>) {
+        let value = self.get(path);
+        let index = value.index;
+        (value, index)
+    }
+
+    /// Returns the raw json of the key and value, such as `"first":"Tom"`,
+    /// when the value is a member of an object.
+    /// This is useful for removing or replacing an entire member.
+    pub fn with_key_raw(&self) -> Option<&str> {
+        if !self.member.is_empty() {
+            Some(&self.member)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the byte offset of the value in the original json, when known.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Returns the position of the value in its parent array, or object,
+    /// when it was found by an array index or a query, such as `0` for
+    /// `friends.#(last=="Murphy")`.
+    pub fn ordinal(&self) -> Option<usize> {
+        self.ordinal
+    }
+
+    /// Returns the number of elements in the parent array, including the
+    /// value itself, when the value was found by an array index or a query
+    /// using `get_with_meta`, such as `3` for `children.1`.
+    pub fn sibling_count(&self) -> Option<usize> {
+        self.siblings
+    }
+
     pub fn exists(&self) -> bool {
         self.json().len() > 0
     }
@@ -207,6 +442,32 @@ impl<'a> Value<'a> {
         KINDMAP[(self.info << 24 >> 24) as usize]
     }
 
+    /// Returns true when the value is an object or an array.
+    pub fn is_container(&self) -> bool {
+        matches!(self.kind(), Kind::Object | Kind::Array)
+    }
+
+    /// Returns true when the value is not an object or an array, which
+    /// includes a non-existent value.
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+
+    /// Returns true for an empty object or array, such as `[]` or `{ }`, an
+    /// empty string, and a non-existent value.
+    /// Unlike counting the children, the raw json is only checked for
+    /// whitespace between the brackets.
+    pub fn is_empty(&self) -> bool {
+        match self.kind() {
+            Kind::Object | Kind::Array => {
+                let raw = trim(self.json().as_bytes());
+                raw.len() >= 2 && raw[1..raw.len() - 1].iter().all(|&c| c <= b' ')
+            }
+            Kind::String => self.str().is_empty(),
+            _ => !self.exists(),
+        }
+    }
+
     pub fn json(&self) -> &str {
         if self.owned.len() > 0 {
             self.owned.as_str()
@@ -215,6 +476,39 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Returns an owned copy of the raw json.
+    pub fn owned_json(&self) -> String {
+        self.json().to_owned()
+    }
+
+    /// Returns an owned copy of the raw json bytes. A string is returned in
+    /// its raw quoted form, such as `"hello"`.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.json().as_bytes().to_vec()
+    }
+
+    /// Returns an owned copy of the string, unescaped. See `str`.
+    pub fn owned_str(&self) -> String {
+        self.str().to_owned()
+    }
+
+    /// Returns the raw json without any leading or trailing whitespace.
+    /// The raw json of an object or array returned from `parse` includes
+    /// everything that follows it in the input.
+    pub fn raw_trimmed(&self) -> &str {
+        tostr(trim(self.json().as_bytes()))
+    }
+
+    /// Returns the exact text of a number, such as `10.10` or `-1e3`, without
+    /// converting it to a float. Returns `None` when the value is not a number.
+    pub fn number_str(&self) -> Option<&str> {
+        if self.kind() == Kind::Number {
+            Some(self.json())
+        } else {
+            None
+        }
+    }
+
     pub fn f64(&'a self) -> f64 {
         let raw = self.json().as_bytes();
         match self.kind() {
@@ -235,6 +529,11 @@ impl<'a> Value<'a> {
         self.f64() as f32
     }
 
+    /// Returns the value as an i64.
+    ///
+    /// A number with a fraction or exponent, such as `2.5` or `2.5e2`, is
+    /// converted to a float first and then truncated toward zero, so `2.5`
+    /// becomes `2`. Use `i64_exact` to reject numbers that aren't integers.
     pub fn i64(&'a self) -> i64 {
         let raw = self.json().as_bytes();
         match self.kind() {
@@ -251,6 +550,42 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Returns a number, or a string containing a number that may be written
+    /// with thousands separators or a currency symbol, such as `"1,234.56"`,
+    /// `"1_000"`, or `"$5.00"`, as an f64.
+    /// Returns `None` for any other value, including a string that is still
+    /// not a number once the separators and symbols are removed.
+    /// Unlike `f64`, which is zero for such strings, this never guesses.
+    pub fn coerce_f64(&self) -> Option<f64> {
+        match self.kind() {
+            Kind::Number => Some(self.f64()),
+            Kind::String => sanitized_f64(self.str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an i64 only when it is exactly an integer that
+    /// fits in an i64, such as `42`, `1e3`, or `2.5e2`. Returns `None` for
+    /// numbers with a fractional part, such as `2.5`, and for any value that
+    /// isn't a number.
+    pub fn i64_exact(&self) -> Option<i64> {
+        if self.kind() != Kind::Number {
+            return None;
+        }
+        let raw = self.json().as_bytes();
+        let (num, info, _) = scan_number(raw, 0);
+        let num = tostr(num);
+        if info & (INFO_DOT | INFO_E) == 0 {
+            return num.parse().ok();
+        }
+        let f = raw_to_f64(num);
+        if f.fract() == 0.0 && (-9223372036854775808.0..9223372036854775808.0).contains(&f) {
+            Some(f as i64)
+        } else {
+            None
+        }
+    }
+
     pub fn u64(&'a self) -> u64 {
         let raw = self.json().as_bytes();
         match self.kind() {
@@ -330,61 +665,766 @@ impl<'a> Value<'a> {
         }
     }
 
-    pub fn str(&'a self) -> &'a str {
-        match self.kind() {
-            Kind::True => "true",
-            Kind::False => "false",
-            Kind::Object | Kind::Array | Kind::Number => self.json(),
-            Kind::String => {
-                if self.info & INFO_ESC == INFO_ESC {
-                    self.uescstr.as_ref()
-                } else {
-                    let raw = self.json().as_bytes();
-                    tostr(&raw[1..raw.len() - 1])
-                }
+    pub fn str(&'a self) -> &'a str {
+        match self.kind() {
+            Kind::True => "true",
+            Kind::False => "false",
+            Kind::Object | Kind::Array | Kind::Number => self.json(),
+            Kind::String => {
+                if self.info & INFO_ESC == INFO_ESC {
+                    self.uescstr.as_ref()
+                } else {
+                    let raw = self.json().as_bytes();
+                    tostr(&raw[1..raw.len() - 1])
+                }
+            }
+            // Return an empty string for null. Use raw() to return the
+            // raw json.
+            Kind::Null => "",
+        }
+    }
+
+    /// Returns the boolean only when the value is `true` or `false`.
+    /// Unlike `bool`, strings such as `"true"` and numbers such as `1` are
+    /// never converted and `None` is returned instead.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind() {
+            Kind::True => Some(true),
+            Kind::False => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns the unescaped string only when the value is a string.
+    /// Unlike `str`, numbers, booleans, objects, arrays, and null are never
+    /// converted to text and `None` is returned instead.
+    pub fn as_str(&'a self) -> Option<&'a str> {
+        if self.kind() == Kind::String {
+            Some(self.str())
+        } else {
+            None
+        }
+    }
+
+    pub fn each(&'a self, mut iter: impl FnMut(Value<'a>, Value<'a>) -> bool) {
+        if !self.exists() {
+            return;
+        }
+        let kind = self.kind();
+        if kind != Kind::Object && kind != Kind::Array {
+            iter(Value::default(), json_clone_from_ref(self));
+            return;
+        }
+        let json = self.json().as_bytes();
+        for_each(json, 0, Lines::Off, kind, iter);
+    }
+
+    /// Returns an iterator over the children of an object or array, yielding
+    /// the zero-based position, key, and value of each child.
+    /// Array elements have no key, so the key is a non-existent value.
+    /// Same as `each`, a scalar is yielded as a single child with no key,
+    /// and nothing is yielded for a non-existent value.
+    ///
+    /// ```
+    /// let value = gjson::parse(r#"{"a":1,"b":2}"#);
+    /// for (i, key, value) in value.enumerate_entries() {
+    ///     println!("{}: {}={}", i, key, value);
+    /// }
+    /// ```
+    pub fn enumerate_entries(&'a self) -> Entries<'a> {
+        let kind = self.kind();
+        let container = self.is_container();
+        Entries {
+            json: if container { self.json().as_bytes() } else { &[] },
+            i: 1,
+            kind,
+            ordinal: 0,
+            single: if !container && self.exists() {
+                Some(json_clone_from_ref(self))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Same as `each`, but returns true when every child was passed to the
+    /// iterator, or false when the iterator stopped early by returning false.
+    pub fn try_each(&'a self, mut iter: impl FnMut(Value<'a>, Value<'a>) -> bool) -> bool {
+        let mut completed = true;
+        self.each(|key, value| {
+            completed = iter(key, value);
+            completed
+        });
+        completed
+    }
+
+    /// Returns the value only when it's an object.
+    pub fn as_object(&'a self) -> Option<Value<'a>> {
+        if self.kind() == Kind::Object {
+            Some(json_clone_from_ref(self))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value only when it's an array.
+    /// Unlike `each`, which iterates a scalar as a single element, this makes
+    /// it possible to skip scalars entirely, such as
+    /// `value.as_array().map(|a| a.array())`.
+    pub fn as_array(&'a self) -> Option<Value<'a>> {
+        if self.kind() == Kind::Array {
+            Some(json_clone_from_ref(self))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value as text with one child per line, which is useful
+    /// for printing the results of a multipath.
+    ///
+    /// Array elements are written as their raw json. Object members are
+    /// written as the key, a tab, and the raw json of the value.
+    /// Any other value is returned as its raw json.
+    pub fn to_lines(&'a self) -> String {
+        let kind = self.kind();
+        if kind != Kind::Object && kind != Kind::Array {
+            return self.json().to_owned();
+        }
+        let mut out = String::new();
+        self.each(|key, value| {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            if kind == Kind::Object {
+                out.push_str(key.str());
+                out.push('\t');
+            }
+            out.push_str(value.json());
+            true
+        });
+        out
+    }
+
+    /// Visits the value and every object member and array element nested in
+    /// it, depth-first, passing the path to each visited value and the value.
+    ///
+    /// The path is the object keys and array indexes leading to the value,
+    /// which is empty for the value itself.
+    ///
+    /// ```
+    /// let value = gjson::parse(r#"{"a":[1,{"b":2}]}"#);
+    /// let mut paths = Vec::new();
+    /// value.walk(|path, _| paths.push(path.join(".")));
+    /// assert_eq!(paths, ["", "a", "a.0", "a.1", "a.1.b"]);
+    /// ```
+    pub fn walk(&self, mut f: impl FnMut(&[String], &Value)) {
+        let mut path = Vec::new();
+        walk(self, &mut path, &mut f);
+    }
+
+    /// Same as `each`, but only the object members with a key matching the
+    /// wildcard pattern, such as `user_*`, are passed to the iterator.
+    /// Arrays and scalars have no keys, so nothing is iterated for them.
+    ///
+    /// Each matching member is passed to the iterator as soon as it's found,
+    /// borrowing from the original json, and no array of the matches is
+    /// built. This makes it suitable for streaming over very large objects,
+    /// unlike a `user_*#` path, which collects the matches into a new array.
+    pub fn each_matching(
+        &'a self,
+        pattern: &str,
+        mut iter: impl FnMut(Value<'a>, Value<'a>) -> bool,
+    ) {
+        if self.kind() != Kind::Object {
+            return;
+        }
+        self.each(|key, value| {
+            if pmatch(pattern, key.str()) {
+                iter(key, value)
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Same as `each`, but only the raw json of each array element, or of
+    /// each object member value, is passed to the iterator.
+    ///
+    /// No `Value` is constructed and no strings are unescaped, which makes
+    /// this faster for copying elements in bulk.
+    pub fn for_each_raw(&self, mut iter: impl FnMut(&[u8]) -> bool) {
+        if !self.exists() {
+            return;
+        }
+        let kind = self.kind();
+        let json = self.json().as_bytes();
+        if kind != Kind::Object && kind != Kind::Array {
+            iter(json);
+            return;
+        }
+        let mut i = 1;
+        let mut index = 0;
+        while i < json.len() {
+            if json[i] <= b' ' || json[i] == b',' || json[i] == b':' {
+                i += 1;
+                continue;
+            }
+            let len = match json[i] {
+                b'}' | b']' => break,
+                b'"' | b'{' | b'[' | b'-' | b'0'..=b'9' => 0,
+                b't' | b'n' => 4,
+                b'f' => 5,
+                // unknown character
+                _ => break,
+            };
+            let s = i;
+            i = proc_value(json, i, Path::default(), false).1;
+            if len > 0 && i - s != len {
+                // incomplete literal
+                break;
+            }
+            // object keys are skipped
+            if (kind == Kind::Array || index % 2 == 1) && !iter(&json[s..i]) {
+                break;
+            }
+            index += 1;
+        }
+    }
+
+    /// Returns true if both values have the same structure and contents.
+    /// Unlike `==`, objects are compared by their members regardless of the
+    /// order of their keys. Arrays are compared element by element.
+    pub fn structurally_eq(&self, other: &Value) -> bool {
+        json_structurally_eq(self.json(), other.json())
+    }
+
+    /// Returns true if both values have the same raw json once whitespace
+    /// is removed, such that `{ "a": 1 }` equals `{"a":1}`.
+    /// Unlike `structurally_eq`, the order of object keys must be the same.
+    pub fn eq_raw_normalized(&self, other: &Value) -> bool {
+        pretty::ugly(self.json()) == pretty::ugly(other.json())
+    }
+
+    /// Returns true if both values are numbers that differ by no more than
+    /// `epsilon`. Values of other kinds fall back to `==`.
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        if self.kind() == Kind::Number && other.kind() == Kind::Number {
+            (self.f64() - other.f64()).abs() <= epsilon
+        } else {
+            self == other
+        }
+    }
+
+    /// Same as `each`, but the zero-based position of each child is passed
+    /// to the iterator in place of the key.
+    pub fn each_indexed(&'a self, mut iter: impl FnMut(usize, Value<'a>) -> bool) {
+        let mut index = 0;
+        self.each(|_, value| {
+            let ok = iter(index, value);
+            index += 1;
+            ok
+        });
+    }
+
+    pub fn array(&'a self) -> Vec<Value<'a>> {
+        let mut arr = Vec::new();
+        if self.kind() == Kind::Array {
+            self.each(|_, value| {
+                arr.push(value);
+                true
+            })
+        }
+        arr
+    }
+
+    /// Returns the members of an object as a map, using the interner for the
+    /// keys. When a key appears more than once, the last value is used.
+    /// Returns an empty map when the value isn't an object.
+    pub fn map_with_interner(
+        &'a self,
+        interner: &mut KeyInterner,
+    ) -> HashMap<Arc<str>, Value<'a>> {
+        let mut map = HashMap::new();
+        if self.kind() == Kind::Object {
+            self.each(|key, value| {
+                map.insert(interner.intern(key.str()), value);
+                true
+            });
+        }
+        map
+    }
+
+    /// Returns the elements of an array as floats, such as for `[1,2,3]`.
+    /// Elements that aren't numbers are skipped when skip is true, otherwise
+    /// they are converted using `f64`, which is zero for most non-numbers.
+    /// Returns an empty vector when the value isn't an array.
+    pub fn f64_array(&'a self, skip: bool) -> Vec<f64> {
+        let mut arr = Vec::new();
+        for value in self.array() {
+            if !skip || value.kind() == Kind::Number {
+                arr.push(value.f64());
+            }
+        }
+        arr
+    }
+
+    /// Returns the elements of an array as strings, such as for `["a","b"]`.
+    /// Each element is converted using `str`.
+    /// Returns an empty vector when the value isn't an array.
+    pub fn str_array(&'a self) -> Vec<String> {
+        self.array().iter().map(|value| value.str().to_owned()).collect()
+    }
+
+    /// Returns the approximate number of heap bytes held by the value, for
+    /// budgeting a cache of values.
+    /// This is the capacity of each buffer that the value owns, such as the
+    /// json produced by a modifier, the unescaped copy of an escaped string,
+    /// and the raw key and value. Json that is borrowed, as for a value
+    /// returned from `get`, is counted by its raw length instead.
+    pub fn heap_size(&self) -> usize {
+        let uescstr = match &self.uescstr {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(uescstr) => uescstr.capacity(),
+        };
+        let member = match &self.member {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(member) => member.capacity(),
+        };
+        self.slice.len() + self.owned.capacity() + uescstr + member
+    }
+
+    /// Returns an owned copy of the value that does not borrow the original
+    /// json, allowing for it to be sent to another thread.
+    pub fn detach(&self) -> OwnedValue {
+        OwnedValue {
+            json: self.json().to_owned(),
+            uescstr: self.uescstr.to_string(),
+            info: self.info,
+            index: self.index,
+            ordinal: self.ordinal,
+            siblings: self.siblings,
+            member: self.member.to_string(),
+        }
+    }
+}
+
+/// PathMiss is the error returned from `try_get` when the path does not
+/// exist in the json.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMiss {
+    path: String,
+}
+
+impl PathMiss {
+    fn new(path: &str) -> PathMiss {
+        PathMiss {
+            path: path.to_owned(),
+        }
+    }
+
+    /// Returns the path that was not found.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for PathMiss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "path not found: {}", self.path)
+    }
+}
+
+impl std::error::Error for PathMiss {}
+
+/// IntoJsonLiteral converts a Rust value into json text, such as the default
+/// passed to `get_or_literal`.
+pub trait IntoJsonLiteral {
+    fn into_json_literal(self) -> String;
+}
+
+impl IntoJsonLiteral for &str {
+    fn into_json_literal(self) -> String {
+        util::escape(self)
+    }
+}
+
+impl IntoJsonLiteral for String {
+    fn into_json_literal(self) -> String {
+        util::escape(&self)
+    }
+}
+
+impl IntoJsonLiteral for bool {
+    fn into_json_literal(self) -> String {
+        self.to_string()
+    }
+}
+
+impl IntoJsonLiteral for f64 {
+    // Json has no NaN or infinity, so those become null.
+    fn into_json_literal(self) -> String {
+        if self.is_finite() {
+            self.to_string()
+        } else {
+            "null".to_owned()
+        }
+    }
+}
+
+impl IntoJsonLiteral for f32 {
+    fn into_json_literal(self) -> String {
+        if self.is_finite() {
+            self.to_string()
+        } else {
+            "null".to_owned()
+        }
+    }
+}
+
+macro_rules! impl_into_json_literal_int {
+    ($($t:ty),*) => {
+        $(
+            impl IntoJsonLiteral for $t {
+                fn into_json_literal(self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_into_json_literal_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<'a> IntoJsonLiteral for Value<'a> {
+    fn into_json_literal(self) -> String {
+        self.json().to_owned()
+    }
+}
+
+/// OwnedValue is a JSON value that owns its data.
+/// It's returned from the `Value::detach` function.
+#[derive(Clone, Default)]
+pub struct OwnedValue {
+    json: String,
+    uescstr: String,
+    info: InfoBits,
+    index: Option<usize>,
+    ordinal: Option<usize>,
+    siblings: Option<usize>,
+    member: String,
+}
+
+impl OwnedValue {
+    /// Returns a `Value` that borrows from this owned value.
+    pub fn borrow(&self) -> Value<'_> {
+        Value {
+            slice: &self.json,
+            owned: String::new(),
+            uescstr: Cow::Borrowed(&self.uescstr),
+            info: self.info,
+            index: self.index,
+            ordinal: self.ordinal,
+            siblings: self.siblings,
+            member: Cow::Borrowed(&self.member),
+        }
+    }
+
+    /// Returns the approximate number of heap bytes held by the value, which
+    /// is the capacity of its json, the unescaped copy of an escaped string,
+    /// and the raw key and value.
+    pub fn heap_size(&self) -> usize {
+        self.json.capacity() + self.uescstr.capacity() + self.member.capacity()
+    }
+}
+
+impl fmt::Display for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.borrow())
+    }
+}
+
+/// Entries is an iterator over the members of an object, or the elements of
+/// an array, yielding the position, key, and value of each.
+/// It's returned from the `Value::enumerate_entries` function.
+pub struct Entries<'a> {
+    json: &'a [u8],
+    i: usize,
+    kind: Kind,
+    ordinal: usize,
+    // A scalar, which is yielded as the only entry.
+    single: Option<Value<'a>>,
+}
+
+impl<'a> Entries<'a> {
+    // next_value returns the next key or value, or None at the end of the
+    // object or array.
+    fn next_value(&mut self) -> Option<Value<'a>> {
+        while self.i < self.json.len() {
+            let c = self.json[self.i];
+            if c <= b' ' || c == b',' || c == b':' {
+                self.i += 1;
+                continue;
+            }
+            if c == b'}' || c == b']' {
+                self.i = self.json.len();
+                break;
+            }
+            let (res, next_i, _) = proc_value(self.json, self.i, Path::default(), true);
+            self.i = next_i;
+            if res.exists() {
+                return Some(res);
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = (usize, Value<'a>, Value<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.single.take() {
+            return Some((0, Value::default(), value));
+        }
+        let key = if self.kind == Kind::Object {
+            self.next_value()?
+        } else {
+            Value::default()
+        };
+        let value = self.next_value()?;
+        let ordinal = self.ordinal;
+        self.ordinal += 1;
+        Some((ordinal, key, value))
+    }
+}
+
+/// KeyInterner stores a single shared copy of each object key that it has
+/// seen, allowing for keys to be reused across many documents that have the
+/// same schema, rather than allocating them again for each document.
+///
+/// ```
+/// let mut interner = gjson::KeyInterner::new();
+/// let a = gjson::parse(r#"{"id":1,"name":"Tom"}"#);
+/// let b = gjson::parse(r#"{"id":2,"name":"Sam"}"#);
+/// let a = a.map_with_interner(&mut interner);
+/// let b = b.map_with_interner(&mut interner);
+/// assert_eq!(b["name"].str(), "Sam");
+/// assert_eq!(interner.len(), 2);
+/// ```
+#[derive(Clone, Default)]
+pub struct KeyInterner {
+    keys: HashSet<Arc<str>>,
+}
+
+impl KeyInterner {
+    pub fn new() -> KeyInterner {
+        KeyInterner::default()
+    }
+
+    /// Returns the shared copy of key, adding it when it hasn't been seen.
+    pub fn intern(&mut self, key: &str) -> Arc<str> {
+        if let Some(key) = self.keys.get(key) {
+            return key.clone();
+        }
+        let key: Arc<str> = Arc::from(key);
+        self.keys.insert(key.clone());
+        key
+    }
+
+    /// Returns the number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes every key.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+/// CompiledPath is a path that is parsed once and may then be used to search
+/// many json documents.
+///
+/// ```
+/// let path = gjson::CompiledPath::new("name.last");
+/// for json in [r#"{"name":{"last":"Anderson"}}"#, r#"{"name":{"last":"Smith"}}"#] {
+///     println!("{}", path.get(json));
+/// }
+/// ```
+///
+/// A path that is a series of keys and indexes separated by dots, such as
+/// `friends.1.last`, is searched using its precompiled components. Any other
+/// path is searched in the same way as `get`.
+#[derive(Clone)]
+pub struct CompiledPath {
+    path: String,
+    comps: Option<Vec<CompiledComp>>,
+}
+
+#[derive(Clone)]
+struct CompiledComp {
+    start: usize,
+    end: usize,
+    esc: bool,
+    pat: bool,
+}
+
+impl CompiledPath {
+    /// Compiles the path.
+    pub fn new(path: &str) -> CompiledPath {
+        CompiledPath {
+            path: path.to_owned(),
+            comps: compile_comps(path),
+        }
+    }
+
+    /// Returns the path that was compiled.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Searches json for the compiled path.
+    /// Works the same as `get`.
+    pub fn get<'a>(&'a self, json: &'a str) -> Value<'a> {
+        let comps = match &self.comps {
+            Some(comps) => comps,
+            None => return get(json, &self.path),
+        };
+        let mut json = json.as_bytes();
+        let mut index = 0;
+        for (n, comp) in comps.iter().enumerate() {
+            let path = Path {
+                comp: &self.path.as_bytes()[comp.start..comp.end],
+                esc: comp.esc,
+                pat: comp.pat,
+                sep: 0,
+                marg: 0,
+                extra: b"",
+                meta: false,
+                siblings: None,
+                depth: None,
+            };
+            let mut i = 0;
+            while i < json.len() && json[i] <= b' ' {
+                i += 1;
             }
-            // Return an empty string for null. Use raw() to return the
-            // raw json.
-            Kind::Null => "",
+            let mut res = if i == json.len() {
+                return Value::default();
+            } else if json[i] == b'{' {
+                get_obj(json, i, path).0
+            } else if json[i] == b'[' {
+                get_arr(json, i, Lines::Off, path).0
+            } else {
+                return Value::default();
+            };
+            if !res.exists() {
+                return Value::default();
+            }
+            // the index is relative to the current json
+            index += res.index.unwrap_or(0);
+            if n == comps.len() - 1 {
+                res.index = Some(index);
+                return res;
+            }
+            json = res.slice.as_bytes();
         }
+        Value::default()
     }
+}
 
-    pub fn each(&'a self, mut iter: impl FnMut(Value<'a>, Value<'a>) -> bool) {
-        if !self.exists() {
-            return;
+// compile_comps returns the components of a path that is a series of keys and
+// indexes separated by dots, or None for any other path.
+fn compile_comps(path: &str) -> Option<Vec<CompiledComp>> {
+    if path.starts_with("..") {
+        return None;
+    }
+    let mut comps = Vec::new();
+    let mut p = Path::new(path);
+    loop {
+        if p.comp.is_empty()
+            || p.comp[0] == b'#'
+            || p.is_modifier()
+            || p.is_multipath()
+            || p.is_wildcard_all()
+        {
+            return None;
         }
-        let kind = self.kind();
-        if kind != Kind::Object && kind != Kind::Array {
-            iter(Value::default(), json_clone_from_ref(&self));
-            return;
+        let end = path.len() - p.extra.len() - if p.sep == 0 { 0 } else { 1 };
+        comps.push(CompiledComp {
+            start: end - p.comp.len(),
+            end,
+            esc: p.esc,
+            pat: p.pat,
+        });
+        match p.sep {
+            0 => return Some(comps),
+            b'.' => p = p.next(),
+            _ => return None,
         }
-        let json = self.json().as_bytes();
-        for_each(json, 0, false, kind, iter);
     }
+}
 
-    pub fn array(&'a self) -> Vec<Value<'a>> {
-        let mut arr = Vec::new();
-        if self.kind() == Kind::Array {
-            self.each(|_, value| {
-                arr.push(value);
+fn json_structurally_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (parse(a), parse(b));
+    match (a.kind(), b.kind()) {
+        (Kind::Object, Kind::Object) => {
+            // Duplicate keys are resolved by using the last value.
+            let mut amap = HashMap::new();
+            a.each(|key, value| {
+                amap.insert(key.str().to_owned(), value);
                 true
-            })
+            });
+            let mut bmap = HashMap::new();
+            b.each(|key, value| {
+                bmap.insert(key.str().to_owned(), value);
+                true
+            });
+            amap.len() == bmap.len()
+                && amap.iter().all(|(key, value)| match bmap.get(key) {
+                    Some(other) => json_structurally_eq(value.json(), other.json()),
+                    None => false,
+                })
         }
-        arr
+        (Kind::Array, Kind::Array) => {
+            let (a, b) = (a.array(), b.array());
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| json_structurally_eq(a.json(), b.json()))
+        }
+        _ => a == b,
     }
 }
 
+// Lines is how for_each reads json. JSON Lines are values that are each on
+// their own line, rather than in an array, and may have comment lines that
+// are skipped.
+#[derive(Copy, Clone, PartialEq)]
+enum Lines {
+    Off,
+    On,
+    Comments,
+}
+
+// COMMENT_PREFIX starts a comment line in JSON Lines that are read with
+// `get_lines_with_comments`.
+const COMMENT_PREFIX: u8 = b'#';
+
 fn for_each<'a>(
     json: &'a [u8],
     mut i: usize,
-    lines: bool,
+    lines: Lines,
     kind: Kind,
     mut iter: impl FnMut(Value<'a>, Value<'a>) -> bool,
 ) -> usize {
     if i == json.len() {
         return i;
     }
-    if !lines {
+    if lines == Lines::Off {
         i += 1;
     }
     let mut index = 0;
@@ -394,6 +1434,13 @@ fn for_each<'a>(
             i += 1;
             continue;
         }
+        if lines == Lines::Comments && json[i] == COMMENT_PREFIX {
+            // skip the rest of the line
+            while i < json.len() && json[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
         if json[i] == b'}' || json[i] == b']' {
             return i + 1;
         }
@@ -421,6 +1468,68 @@ fn for_each<'a>(
     i
 }
 
+// get_all_values appends every value in json that matches the path to all.
+// Plain key and index components are followed into each matching child.
+// Any other component, such as a query or modifier, is handed off to `get`
+// with the rest of the path.
+fn get_all_values<'a>(
+    json: &'a str,
+    index: Option<usize>,
+    path: &'a str,
+    all: &mut Vec<Value<'a>>,
+) {
+    let offset = |res: &Value| index.and_then(|a| res.index.map(|b| a + b));
+    let comp = Path::new(path);
+    let plain = match comp.comp.first() {
+        None | Some(b'#' | b'@' | b'{' | b'[' | b'!' | b'.') => false,
+        _ => !comp.is_multipath() && !comp.is_wildcard_all(),
+    };
+    if !plain {
+        let mut res = get(json, path);
+        if res.exists() {
+            res.index = offset(&res);
+            all.push(res);
+        }
+        return;
+    }
+    let rest = if comp.more() { tostr(comp.extra) } else { "" };
+    let bytes = json.as_bytes();
+    let i = match bytes.iter().position(|&c| c > b' ') {
+        Some(i) => i,
+        None => return,
+    };
+    let kind = match bytes[i] {
+        b'{' => Kind::Object,
+        b'[' => Kind::Array,
+        _ => return,
+    };
+    let nth = if kind == Kind::Array {
+        match tostr(comp.comp).parse::<usize>() {
+            Ok(n) => Some(n),
+            Err(_) => return,
+        }
+    } else {
+        None
+    };
+    let mut pos = 0;
+    for_each(bytes, i, Lines::Off, kind, |key, mut res| {
+        let matched = match nth {
+            Some(n) => pos == n,
+            None => key_match(key.json().as_bytes(), key.info, &comp),
+        };
+        pos += 1;
+        if matched {
+            res.index = offset(&res);
+            if rest.is_empty() {
+                all.push(res);
+            } else {
+                get_all_values(res.slice, res.index, rest, all);
+            }
+        }
+        !matches!(nth, Some(n) if pos > n)
+    });
+}
+
 fn raw_to_f64(raw: &str) -> f64 {
     raw.parse().unwrap_or(0.0)
 }
@@ -588,10 +1697,14 @@ fn proc_value<'a>(
                 let next_path = path.next();
                 if !next_path.is_modifier() && !next_path.is_multipath() {
                     squash = false;
+                    let next_path = match next_path.enter() {
+                        Some(next_path) => next_path,
+                        None => return (Value::default(), json.len(), Path::default()),
+                    };
                     let (res, next_i, next_path) = if json[i] == b'{' {
                         get_obj(json, i, next_path)
                     } else {
-                        get_arr(json, i, false, next_path)
+                        get_arr(json, i, Lines::Off, next_path)
                     };
                     if res.exists() {
                         return (res, next_i, next_path);
@@ -645,6 +1758,11 @@ fn proc_value<'a>(
             // unknown character
             return (Value::default(), json.len(), Path::default());
         }
+        if i < json.len() && json[i] & 0xC0 == 0x80 {
+            // The literal is malformed and ends in the middle of a multibyte
+            // character, which must never be sliced.
+            return (Value::default(), json.len(), path);
+        }
         if is_match {
             return (json_from_slice(&json[s..i], Some(s), kind), i, path);
         }
@@ -656,6 +1774,18 @@ fn get_obj<'a>(json: &'a [u8], mut i: usize, path: Path<'a>) -> (Value<'a>, usiz
     if i == json.len() || json[i] != b'{' {
         return (Value::default(), i, path);
     }
+    if path.is_wildcard_all() {
+        let pattern = tostr(&path.comp[..path.comp.len() - 1]);
+        return get_children_with_subpath(json, i, Lines::Off, Kind::Object, path, Some(pattern));
+    }
+    if path.comp.len() > 1 && path.comp[0] == b'#' && path.comp[1] == b'(' {
+        // Query the values of the object, such as `attributes.#(>0)`.
+        return if path.comp[path.comp.len() - 1] == b'#' {
+            get_children_with_query_subpath(json, i, Lines::Off, Kind::Object, path)
+        } else {
+            get_child_with_query(json, i, Lines::Off, Kind::Object, path)
+        };
+    }
     i += 1;
     while i < json.len() {
         if json[i] == b'}' {
@@ -667,6 +1797,7 @@ fn get_obj<'a>(json: &'a [u8], mut i: usize, path: Path<'a>) -> (Value<'a>, usiz
             continue;
         }
         // key
+        let s = i;
         let (key, info, next_i) = scan_string(json, i);
         i = next_i;
         while i < json.len() {
@@ -680,9 +1811,14 @@ fn get_obj<'a>(json: &'a [u8], mut i: usize, path: Path<'a>) -> (Value<'a>, usiz
             break;
         }
         let is_match = key_match(key, info, &path);
-        let (res, next_i, next_path) = proc_value(json, i, path, is_match);
+        let vi = i;
+        let (mut res, next_i, next_path) = proc_value(json, i, path, is_match);
         i = next_i;
         if res.exists() {
+            if res.index == Some(vi) {
+                // The value belongs to this key, rather than a nested key.
+                res.member = Cow::Borrowed(tostr(&json[s..vi + res.slice.len()]));
+            }
             return (res, i, next_path);
         }
     }
@@ -692,6 +1828,21 @@ fn get_obj<'a>(json: &'a [u8], mut i: usize, path: Path<'a>) -> (Value<'a>, usiz
 fn key_match(key: &[u8], info: InfoBits, path: &Path) -> bool {
     let comp = tostr(path.comp);
     if info & INFO_ESC == INFO_ESC {
+        // Rule out most keys before unescaping. An unescaped key is never
+        // longer than its raw form, and a key that doesn't start with an
+        // escape must start with the same byte as the component, unless the
+        // component starts with a wildcard or escape.
+        let raw = &key[1..key.len() - 1];
+        if !(path.pat || path.esc) && comp.len() > raw.len() {
+            return false;
+        }
+        if raw[0] != b'\\' {
+            match path.comp.first() {
+                Some(b'*' | b'?' | b'\\') => {}
+                Some(&c) if c == raw[0] => {}
+                _ => return false,
+            }
+        }
         let key = unescape(tostr(key));
         if path.pat || path.esc {
             pmatch(comp, key)
@@ -711,7 +1862,7 @@ fn key_match(key: &[u8], info: InfoBits, path: &Path) -> bool {
 fn get_arr<'a>(
     json: &'a [u8],
     i: usize,
-    lines: bool,
+    lines: Lines,
     path: Path<'a>,
 ) -> (Value<'a>, usize, Path<'a>) {
     // Array paths are special.
@@ -721,6 +1872,7 @@ fn get_arr<'a>(
     // - Query: Return a single child using a query.
     // - Sub path (recomposition): Creates a new array from child paths.
     // - Query + Sub path (recomp): Create a new array from child querys.
+    // - Wildcard: Same as Sub path, using `*#`, but the sub path is optional.
     // The `lines` param allows for the input to be in JSON Lines format,
     // where, rather than having [value1,value2,value3], each value is on
     // a separate line like:
@@ -729,17 +1881,19 @@ fn get_arr<'a>(
     // value2
     // value3
     // ```
-    if path.comp.len() > 0 && path.comp[0] == b'#' {
+    if path.comp == b"*#" {
+        get_children_with_subpath(json, i, lines, Kind::Array, path, None)
+    } else if !path.comp.is_empty() && path.comp[0] == b'#' {
         if path.comp.len() == 1 {
             if path.sep == b'.' {
-                get_arr_children_with_subpath(json, i, lines, path)
+                get_children_with_subpath(json, i, lines, Kind::Array, path, None)
             } else {
                 get_arr_count(json, i, lines, path)
             }
         } else if path.comp[path.comp.len() - 1] == b'#' {
-            get_arr_children_with_query_subpath(json, i, lines, path)
+            get_children_with_query_subpath(json, i, lines, Kind::Array, path)
         } else {
-            get_arr_child_with_query(json, i, lines, path)
+            get_child_with_query(json, i, lines, Kind::Array, path)
         }
     } else {
         get_arr_child_at_index(json, i, lines, path)
@@ -749,7 +1903,7 @@ fn get_arr<'a>(
 fn get_arr_count<'a>(
     json: &'a [u8],
     mut i: usize,
-    lines: bool,
+    lines: Lines,
     path: Path<'a>,
 ) -> (Value<'a>, usize, Path<'a>) {
     let mut count = 0;
@@ -757,33 +1911,56 @@ fn get_arr_count<'a>(
         count += 1;
         true
     });
-    let res = json_from_owned(format!("{}", count), None, INFO_NUMBER);
+    let res = json_from_owned(fmt_usize(count), None, INFO_NUMBER);
     (res, i, path)
 }
 
 fn get_arr_child_at_index<'a>(
     json: &'a [u8],
-    mut i: usize,
-    lines: bool,
-    path: Path<'a>,
+    i: usize,
+    lines: Lines,
+    mut path: Path<'a>,
 ) -> (Value<'a>, usize, Path<'a>) {
     let comp_index = tostr(path.comp).parse::<i64>().unwrap_or(-1);
+    let count = path.meta && !path.more();
+    let (res, i, n) = arr_child_at(json, i, lines, comp_index, count);
+    if count && res.exists() {
+        path.siblings = Some(n);
+    }
+    (res, i, path)
+}
+
+// arr_child_at returns the array element at the target position, which is
+// a non-existent value when the target is negative or out of range. When
+// count is set, the rest of the array is read and its length is returned.
+fn arr_child_at<'a>(
+    json: &'a [u8],
+    mut i: usize,
+    lines: Lines,
+    target: i64,
+    count: bool,
+) -> (Value<'a>, usize, usize) {
     let mut res = Value::default();
     let mut index = 0;
     let mut next_i = 0;
     i = for_each(json, i, lines, Kind::Array, |_, value| {
-        if index == comp_index {
+        if index == target {
             res = value;
+            res.ordinal = Some(index as usize);
             next_i = i;
-            return false;
+            if !count {
+                return false;
+            }
         }
         index += 1;
         true
     });
-    if res.exists() {
-        (res, next_i, path)
+    if !res.exists() {
+        (Value::default(), i, 0)
+    } else if count {
+        (res, i, index as usize)
     } else {
-        (Value::default(), i, path)
+        (res, next_i, 0)
     }
 }
 
@@ -806,7 +1983,21 @@ fn query_matches<'a>(valin: &Value<'a>, op: &str, rpv: &str) -> bool {
     }
     let mut value = valin;
     let mut tvalue = Value::default();
-    if rpv.len() > 0 && rpv[0] == b'~' {
+    let mut op = op;
+    if op.len() > 1 && op.ends_with('#') {
+        // a numeric comparison, such as `>#`, converts a string to the number
+        // at its start, such as "44kg" to 44. Other kinds are left as-is.
+        op = &op[..op.len() - 1];
+        if value.kind() == Kind::String {
+            match leading_f64(value.str()) {
+                Some(n) => tvalue = json_from_owned(format!("{}", n), None, INFO_NUMBER),
+                None => return false,
+            }
+            value = &tvalue;
+        }
+    }
+    let regex = op == "~" || op == "!~";
+    if !regex && !rpv.is_empty() && rpv[0] == b'~' {
         // convert to bool
         rpv = &rpv[1..];
         if value.bool() {
@@ -829,6 +2020,19 @@ fn query_matches<'a>(valin: &Value<'a>, op: &str, rpv: &str) -> bool {
         // "name" that exists
         return true;
     }
+    if op == "~==" {
+        // loose equality compares numbers, and strings that are numbers, by
+        // their numeric value. Everything else is compared as text.
+        let text = if value.kind() == Kind::String {
+            value.str()
+        } else {
+            value.json()
+        };
+        return match (loose_f64(text), loose_f64(rpv)) {
+            (Some(a), Some(b)) => a == b,
+            _ => text == rpv,
+        };
+    }
     match value.kind() {
         Kind::String => match op {
             "=" => value.str() == rpv,
@@ -839,6 +2043,8 @@ fn query_matches<'a>(valin: &Value<'a>, op: &str, rpv: &str) -> bool {
             ">=" => value.str() >= rpv,
             "%" => pmatch(rpv, value.str()),
             "!%" => !pmatch(rpv, value.str()),
+            "~" => regex_match(rpv, value.str()) == Some(true),
+            "!~" => regex_match(rpv, value.str()) == Some(false),
             _ => false,
         },
         Kind::Number => {
@@ -871,56 +2077,149 @@ fn query_matches<'a>(valin: &Value<'a>, op: &str, rpv: &str) -> bool {
     }
 }
 
-fn get_arr_child_with_query<'a>(
+// regex_match returns true when the regular expression matches the text.
+// Compiled expressions are cached for each thread. Returns None when the
+// expression is not valid.
+#[cfg(feature = "regex")]
+fn regex_match(pattern: &str, text: &str) -> Option<bool> {
+    use std::cell::RefCell;
+    thread_local! {
+        static CACHE: RefCell<HashMap<String, Option<regex::Regex>>> =
+            RefCell::new(HashMap::new());
+    }
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(re) = cache.get(pattern) {
+            return re.as_ref().map(|re| re.is_match(text));
+        }
+        if cache.len() >= 64 {
+            cache.clear();
+        }
+        let re = regex::Regex::new(pattern).ok();
+        let res = re.as_ref().map(|re| re.is_match(text));
+        cache.insert(pattern.to_owned(), re);
+        res
+    })
+}
+
+// regex_supported returns false when the conditions use the `~` or `!~`
+// operators without the "regex" feature, in which case the query is rejected
+// rather than never, or always, matching.
+fn regex_supported(conds: &[Vec<(&str, &str, &str)>]) -> bool {
+    cfg!(feature = "regex") || !conds.iter().flatten().any(|&(_, op, _)| op == "~" || op == "!~")
+}
+
+// regex_match always returns None, because regular expressions require the
+// "regex" feature.
+#[cfg(not(feature = "regex"))]
+fn regex_match(_: &str, _: &str) -> Option<bool> {
+    None
+}
+
+// loose_f64 returns the number that s represents, when the entire string is
+// a number.
+fn loose_f64(s: &str) -> Option<f64> {
+    match s.as_bytes().first() {
+        Some(b'0'..=b'9' | b'-' | b'.') => s.parse().ok(),
+        _ => None,
+    }
+}
+
+// query_conditions_match returns true when every condition in any one of the
+// groups matches the value.
+fn query_conditions_match<'a>(
+    value: &'a Value<'a>,
+    conds: &[Vec<(&'a str, &'a str, &'a str)>],
+    depth: Option<usize>,
+) -> bool {
+    conds.iter().any(|group| {
+        group.iter().all(|&(lh, op, rhv)| {
+            if !lh.is_empty() {
+                query_matches(&get_within(value, lh, depth), op, rhv)
+            } else {
+                query_matches(value, op, rhv)
+            }
+        })
+    })
+}
+
+// get_within is the same as `value.get(path)`, but keeps the depth limit that
+// was left when the value was reached.
+fn get_within<'a>(value: &'a Value<'a>, path: &'a str, depth: Option<usize>) -> Value<'a> {
+    if depth.is_none() || path.is_empty() {
+        return value.get(path);
+    }
+    get_with_root(value.json(), path, value.json(), false, depth, false)
+}
+
+fn get_child_with_query<'a>(
     json: &'a [u8],
     mut i: usize,
-    lines: bool,
-    path: Path<'a>,
+    lines: Lines,
+    kind: Kind,
+    mut path: Path<'a>,
 ) -> (Value<'a>, usize, Path<'a>) {
-    let (lh, op, rhv) = path.query_parts();
+    let conds = path.query_conditions();
+    if !regex_supported(&conds) {
+        return (Value::default(), json.len(), path);
+    }
+    let count = path.meta && !path.more() && kind == Kind::Array;
     let mut res = Value::default();
-    i = for_each(json, i, lines, Kind::Array, |_, value| {
-        let is_match = if lh != "" {
-            query_matches(&value.get(lh), op, rhv)
-        } else {
-            query_matches(&value, op, rhv)
-        };
-        if is_match {
+    let mut index = 0;
+    i = for_each(json, i, lines, kind, |_, value| {
+        if !res.exists() && query_conditions_match(&value, &conds, path.depth) {
             res = value;
-            return false;
+            res.ordinal = Some(index);
+            if !count {
+                return false;
+            }
         }
+        index += 1;
         true
     });
     if res.exists() {
+        if count {
+            path.siblings = Some(index);
+        }
         (res, i, path)
     } else {
         (Value::default(), i, path)
     }
 }
 
-fn get_arr_children_with_query_subpath<'a>(
+fn get_children_with_query_subpath<'a>(
     json: &'a [u8],
     mut i: usize,
-    lines: bool,
+    lines: Lines,
+    kind: Kind,
     mut path: Path<'a>,
 ) -> (Value<'a>, usize, Path<'a>) {
-    let (lh, op, rhv) = path.query_parts();
+    let conds = path.query_conditions();
     let mut subpath = None;
     let r = path.next_group();
     if path.sep == b'.' {
         subpath = Some(r.0);
     }
     path = r.1;
+    if !regex_supported(&conds) {
+        return (Value::default(), json.len(), path);
+    }
+    if subpath == Some("#") {
+        // a trailing `#` counts the matches, such as `friends.#(age>40)#.#`.
+        let mut count = 0;
+        i = for_each(json, i, lines, kind, |_, value| {
+            if query_conditions_match(&value, &conds, path.depth) {
+                count += 1;
+            }
+            true
+        });
+        return (json_from_owned(fmt_usize(count), None, INFO_NUMBER), i, path);
+    }
     let mut res = Vec::new();
     res.push(b'[');
     let mut index = 0;
-    i = for_each(json, i, lines, Kind::Array, |_, value| {
-        let is_match = if lh != "" {
-            query_matches(&value.get(lh), op, rhv)
-        } else {
-            query_matches(&value, op, rhv)
-        };
-        if is_match {
+    i = for_each(json, i, lines, kind, |_, value| {
+        if query_conditions_match(&value, &conds, path.depth) {
             let value = if let Some(subpath) = subpath {
                 value.get(subpath)
             } else {
@@ -946,20 +2245,37 @@ fn get_arr_children_with_query_subpath<'a>(
     (res, i, path)
 }
 
-fn get_arr_children_with_subpath<'a>(
+// get_children_with_subpath creates a new array from the child values of an
+// object or array, such as `friends.#.first` or `name.*`. The sub path that
+// follows the component is applied to each child, and is optional.
+fn get_children_with_subpath<'a>(
     json: &'a [u8],
     mut i: usize,
-    lines: bool,
+    lines: Lines,
+    kind: Kind,
     mut path: Path<'a>,
+    pattern: Option<&str>,
 ) -> (Value<'a>, usize, Path<'a>) {
+    let mut subpath = None;
     let r = path.next_group();
-    let subpath = r.0;
+    if path.sep == b'.' {
+        subpath = Some(r.0);
+    }
     path = r.1;
     let mut res = Vec::new();
     res.push(b'[');
     let mut index = 0;
-    i = for_each(json, i, lines, Kind::Array, |_, value| {
-        let value = value.get(subpath);
+    i = for_each(json, i, lines, kind, |key, value| {
+        if let Some(pattern) = pattern {
+            if !pmatch(pattern, key.str()) {
+                return true;
+            }
+        }
+        let value = if let Some(subpath) = subpath {
+            get_within(&value, subpath, path.depth)
+        } else {
+            value
+        };
         if value.exists() {
             if index > 0 {
                 res.push(b',');
@@ -979,6 +2295,25 @@ fn get_arr_children_with_subpath<'a>(
     (res, i, path)
 }
 
+// lines_to_array returns a json array containing each value from a JSON Lines
+// document.
+fn lines_to_array(json: &[u8], lines: Lines) -> String {
+    let mut out = Vec::new();
+    out.push(b'[');
+    let mut index = 0;
+    for_each(json, 0, lines, Kind::Array, |_, value| {
+        if index > 0 {
+            out.push(b',');
+        }
+        out.extend(value.json().as_bytes());
+        index += 1;
+        true
+    });
+    out.push(b']');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
 /// Searches json for the specified path.
 /// A path is in dot syntax, such as "name.last" or "age".
 /// When the value is found it's returned immediately.
@@ -989,6 +2324,7 @@ fn get_arr_children_with_subpath<'a>(
 /// To get the number of elements in an array or to access a child path, use
 /// the '#' character.
 /// The dot and wildcard character can be escaped with '\'.
+/// An empty path returns the entire json document.
 ///
 /// ```json
 /// {
@@ -1019,23 +2355,53 @@ fn get_arr_children_with_subpath<'a>(
 /// use the `valid` function first.
 #[inline]
 pub fn get<'a>(json: &'a str, path: &'a str) -> Value<'a> {
+    if path.is_empty() {
+        // An empty path is the entire json document, the same as `@this`.
+        return parse(json);
+    }
+    get_with_root(json, path, json, false, None, false)
+}
+
+// get_with_root is the same as `get`, but also carries the original json
+// document, which is made available to modifiers such as `@root`. When meta
+// is set, the length of the array that the value was found in by an index or
+// a query is stored in the value too. When depth is set, no more than that many nested
+// objects and arrays are entered. When comments is set, JSON Lines may have
+// comment lines.
+fn get_with_root<'a>(
+    json: &'a str,
+    path: &'a str,
+    root: &'a str,
+    meta: bool,
+    depth: Option<usize>,
+    comments: bool,
+) -> Value<'a> {
     let mut path = path;
-    let mut lines = false;
+    let mut lines = Lines::Off;
     if path.len() >= 2 && path.as_bytes()[0] == b'.' && path.as_bytes()[1] == b'.' {
         // json lines
         path = tostr(&path.as_bytes()[2..]);
-        lines = true;
+        lines = if comments { Lines::Comments } else { Lines::On };
     }
-    let path = Path::new(path);
+    let mut path = Path::new(path);
+    path.meta = meta;
+    path.depth = depth;
     let (res, path) = {
         let json = json.as_bytes();
-        if lines {
-            let res = get_arr(json, 0, true, path);
+        if path.is_modifier() && path.exceeds_depth(json) {
+            (Value::default(), Path::default())
+        } else if lines != Lines::Off && path.is_modifier() {
+            // The modifier is applied to all of the lines as a single array.
+            let arr = lines_to_array(json, lines);
+            let res = modifiers::exec(arr.as_bytes(), root.as_bytes(), path).0;
+            (json_into_owned(res), path)
+        } else if lines != Lines::Off {
+            let res = get_arr(json, 0, lines, path);
             (res.0, res.2)
         } else if path.is_modifier() {
-            modifiers::exec(json, path)
+            modifiers::exec(json, root.as_bytes(), path)
         } else if path.is_multipath() {
-            multipath::exec(json, path)
+            multipath::exec(json, root.as_bytes(), path)
         } else {
             let mut i = 0;
             loop {
@@ -1046,26 +2412,49 @@ pub fn get<'a>(json: &'a str, path: &'a str) -> Value<'a> {
                     i += 1;
                     continue;
                 }
+                let path = match path.enter() {
+                    Some(path) => path,
+                    None => break (Value::default(), Path::default()),
+                };
                 if json[i] == b'{' {
                     let res = get_obj(json, i, path);
                     break (res.0, res.2);
                 }
                 if json[i] == b'[' {
-                    let res = get_arr(json, i, false, path);
+                    let res = get_arr(json, i, Lines::Off, path);
                     break (res.0, res.2);
                 }
                 break (Value::default(), path);
             }
         }
     };
+    let (mut res, mut path) = (res, path);
+    while path.sep == b'|' {
+        // a bare function name, such as `friends|length`, may follow a pipe.
+        // A member with the same key takes precedence, so existing paths such
+        // as `a|length` on `{"a":{"length":5}}` still return the member.
+        let next = path.next();
+        if !next.is_bare_modifier() || has_key(&res, tostr(next.comp)) {
+            break;
+        }
+        if next.exceeds_depth(res.json().as_bytes()) {
+            return Value::default();
+        }
+        let mut json = json_into_owned(modifiers::exec(res.json().as_bytes(), root.as_bytes(), next).0);
+        json.index = res.index.and_then(|index1| json.index.map(|index2| index1 + index2));
+        res = json;
+        path = next;
+    }
     if !path.more() {
+        res.siblings = path.siblings;
         return res;
     }
+    let depth = path.depth;
     let path = tostr(path.extra);
-    let mut json = if res.slice.len() > 0 {
-        get(&res.slice, path)
+    let mut json = if !res.slice.is_empty() {
+        get_with_root(res.slice, path, root, meta, depth, comments)
     } else {
-        json_into_owned(get(&res.owned, path))
+        json_into_owned(get_with_root(&res.owned, path, root, meta, depth, comments))
     };
     let mut index = None;
     if let Some(index1) = res.index {
@@ -1077,6 +2466,248 @@ pub fn get<'a>(json: &'a str, path: &'a str) -> Value<'a> {
     json
 }
 
+// has_key returns true if the value is an object with a member that has the
+// key.
+fn has_key(value: &Value, key: &str) -> bool {
+    let mut found = false;
+    if value.kind() == Kind::Object {
+        value.each(|k, _| {
+            found = k.str() == key;
+            !found
+        });
+    }
+    found
+}
+
+/// Searches json for the specified path, where the components of the path are
+/// separated by sep rather than a dot, such as `fav.movie/0` using a `/`.
+/// Dots in the path are treated as part of a key, and `\` may be used to
+/// escape sep.
+///
+/// Queries, multipaths, and modifier arguments, which are wrapped in
+/// parentheses, brackets, or braces, use the standard syntax, and `|` remains
+/// the pipe character.
+///
+/// ```
+/// let json = r#"{"fav.movie":{"title":"Deer Hunter"}}"#;
+/// let value = gjson::get_sep(json, "fav.movie/title", '/');
+/// assert_eq!(value.str(), "Deer Hunter");
+/// ```
+pub fn get_sep<'a>(json: &'a str, path: &str, sep: char) -> Value<'a> {
+    let path = path::with_separator(path, sep);
+    borrow_from(json, get(json, &path))
+}
+
+/// Searches json for the specified path.
+/// Works the same as `get`, except that the lines of a JSON Lines path, such
+/// as `..#`, that start with a `#`, ignoring leading whitespace, are comments
+/// and skipped.
+///
+/// ```
+/// let json = "# users\n{\"name\":\"Tom\"}\n\n# {\"name\":\"old\"}\n{\"name\":\"Sam\"}\n";
+/// assert_eq!(gjson::get_lines_with_comments(json, "..#").i32(), 2);
+/// assert_eq!(gjson::get_lines_with_comments(json, "..1.name").str(), "Sam");
+/// ```
+///
+/// Blank lines are always skipped, with or without this function.
+pub fn get_lines_with_comments<'a>(json: &'a str, path: &'a str) -> Value<'a> {
+    if path.is_empty() {
+        return parse(json);
+    }
+    get_with_root(json, path, json, false, None, true)
+}
+
+/// Searches json for the specified path, which may be either a standard path
+/// or a JSON Pointer ([RFC 6901](https://tools.ietf.org/html/rfc6901)).
+///
+/// The path is always tried as a standard path first. Only when that does
+/// not find a value, and the path starts with `/`, is it retried as a JSON
+/// Pointer.
+///
+/// ```
+/// let json = r#"{"fav.movie":["Deer Hunter"],"a/b":1}"#;
+/// assert_eq!(gjson::get_any(json, "fav\\.movie.0").str(), "Deer Hunter");
+/// assert_eq!(gjson::get_any(json, "/fav.movie/0").str(), "Deer Hunter");
+/// assert_eq!(gjson::get_any(json, "/a~1b").i32(), 1);
+/// ```
+pub fn get_any<'a>(json: &'a str, path: &'a str) -> Value<'a> {
+    let res = get(json, path);
+    if res.exists() || !path.starts_with('/') {
+        return res;
+    }
+    get_pointer(json, path)
+}
+
+// get_pointer resolves a JSON Pointer one reference token at a time. Unlike
+// converting the pointer to a path, this allows for every key, including the
+// empty key of the pointer `/`.
+fn get_pointer<'a>(json: &'a str, pointer: &str) -> Value<'a> {
+    let mut res = parse(json);
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let kind = res.kind();
+        let nth = if kind == Kind::Array {
+            // An array index has no leading zeros.
+            match token.parse::<usize>() {
+                Ok(n) if token == fmt_usize(n) => Some(n),
+                _ => return Value::default(),
+            }
+        } else if kind == Kind::Object {
+            None
+        } else {
+            return Value::default();
+        };
+        let base = res.index;
+        let mut child = Value::default();
+        let mut pos = 0;
+        for_each(res.slice.as_bytes(), 0, Lines::Off, kind, |key, value| {
+            let found = match nth {
+                Some(n) => pos == n,
+                None => key.str() == token,
+            };
+            pos += 1;
+            if found {
+                child = value;
+            }
+            !found
+        });
+        child.index = base.and_then(|a| child.index.map(|b| a + b));
+        res = child;
+    }
+    res
+}
+
+/// Searches json for the specified path, the same as `get`, and also records
+/// the number of elements in the parent array when the value is found by an
+/// array index or a query. It's returned from `Value::sibling_count`.
+///
+/// ```
+/// let json = r#"{"friends":[{"first":"Dale"},{"first":"Roger"},{"first":"Jane"}]}"#;
+/// let value = gjson::get_with_meta(json, "friends.1");
+/// assert_eq!(value.get("first").str(), "Roger");
+/// assert_eq!(value.sibling_count(), Some(3));
+/// ```
+///
+/// The array is counted in the same pass that finds the value, which means
+/// reading past the value to the end of the array. That's why `get` does not
+/// do it.
+pub fn get_with_meta<'a>(json: &'a str, path: &'a str) -> Value<'a> {
+    if path.is_empty() {
+        return parse(json);
+    }
+    get_with_root(json, path, json, true, None, false)
+}
+
+/// Searches json for a path taken from a URL, where each dot separated
+/// segment may be percent-encoded, such as `name.first%20name`.
+///
+/// The path is split on its dots before the segments are decoded, so an
+/// encoded dot (`%2E`) is part of a key. Every decoded segment is matched as
+/// a literal key or array index, which means that wildcards, queries, and
+/// modifiers are not available.
+///
+/// ```
+/// let json = r#"{"first name":"Tom","fav.movie":"Deer Hunter"}"#;
+/// assert_eq!(gjson::get_urlpath(json, "first%20name").str(), "Tom");
+/// assert_eq!(gjson::get_urlpath(json, "fav%2Emovie").str(), "Deer Hunter");
+/// ```
+pub fn get_urlpath<'a>(json: &'a str, path: &str) -> Value<'a> {
+    borrow_from(json, get(json, &path::from_urlpath(path)))
+}
+
+// borrow_from returns a value that borrows from json, rather than from the
+// path that was used to find it, whenever the value is part of json.
+fn borrow_from<'a>(json: &'a str, res: Value) -> Value<'a> {
+    if let Some(index) = res.index {
+        let end = index + res.json().len();
+        if json.get(index..end) == Some(res.json()) {
+            // The value is part of the original json, so borrow it, and its
+            // key too when that's part of the original json.
+            let start = end.saturating_sub(res.member.len());
+            let member = match json.get(start..end) {
+                Some(member) if member == res.member => Cow::Borrowed(member),
+                _ => Cow::Owned(res.member.into_owned()),
+            };
+            return Value {
+                slice: &json[index..end],
+                owned: String::new(),
+                uescstr: Cow::Owned(res.uescstr.into_owned()),
+                info: res.info,
+                index: res.index,
+                ordinal: res.ordinal,
+                siblings: res.siblings,
+                member,
+            };
+        }
+    }
+    json_into_owned(res)
+}
+
+fn walk(value: &Value, path: &mut Vec<String>, f: &mut dyn FnMut(&[String], &Value)) {
+    if !value.exists() {
+        return;
+    }
+    f(path, value);
+    if value.is_scalar() {
+        return;
+    }
+    let kind = value.kind();
+    let mut index = 0;
+    value.each(|key, child| {
+        if kind == Kind::Object {
+            path.push(key.str().to_owned());
+        } else {
+            path.push(index.to_string());
+        }
+        walk(&child, path, f);
+        path.pop();
+        index += 1;
+        true
+    });
+}
+
+/// Searches json for the specified path.
+/// Works the same as `get` except that a non-existent value is returned when
+/// finding the value means entering objects and arrays nested deeper than
+/// max_depth, when a modifier is given json nested deeper than max_depth, or
+/// when the queries and multipaths in the path are nested deeper than
+/// max_depth.
+///
+/// The limit is checked while searching, so the rest of the json is not
+/// read. A value that is found is returned as-is, even when it's nested
+/// deeper than max_depth itself.
+///
+/// This should be preferred over `get` for untrusted input, because very
+/// deeply nested input may otherwise overflow the stack.
+pub fn get_with_depth<'a>(json: &'a str, path: &'a str, max_depth: usize) -> Value<'a> {
+    if exceeds_depth(path.as_bytes(), max_depth) {
+        return Value::default();
+    }
+    if path.is_empty() {
+        return parse(json);
+    }
+    get_with_root(json, path, json, false, Some(max_depth), false)
+}
+
+/// Searches json for the specified path.
+/// Works the same as `get` except that a `PathMiss` error is returned when
+/// the value does not exist.
+///
+/// ```
+/// fn last_name(json: &str) -> Result<String, gjson::PathMiss> {
+///     let name = gjson::try_get(json, "name")?;
+///     Ok(name.try_get("last")?.str().to_owned())
+/// }
+/// ```
+pub fn try_get<'a>(json: &'a str, path: &'a str) -> Result<Value<'a>, PathMiss> {
+    let value = get(json, path);
+    if value.exists() {
+        Ok(value)
+    } else {
+        Err(PathMiss::new(path))
+    }
+}
+
 /// Searches json for the specified path.
 /// Works the same as `get` except that the input json is a a byte slice
 /// instead of a string.
@@ -1088,25 +2719,62 @@ pub fn get<'a>(json: &'a str, path: &'a str) -> Value<'a> {
 /// results, as the rest of Rust assumes that [`&str`]s in Value<'a> are
 /// valid UTF-8.
 pub unsafe fn get_bytes<'a>(json: &'a [u8], path: &'a str) -> Value<'a> {
-    return get(tostr(json), path)
+    get(tostr(json), path)
+}
+
+/// Searches json for the specified path.
+/// Works the same as `get` except that the path is a byte slice.
+/// A non-existent value is returned when the path is not valid UTF-8.
+pub fn get_path_bytes<'a>(json: &'a str, path: &'a [u8]) -> Value<'a> {
+    match std::str::from_utf8(path) {
+        Ok(path) => get(json, path),
+        Err(_) => Value::default(),
+    }
+}
+
+/// Searches json for the specified path.
+/// Works the same as `get` except that the input json is a byte slice that
+/// may contain invalid UTF-8, which is replaced with the U+FFFD replacement
+/// character before searching.
+///
+/// The returned value owns its data, because the replacement may require a
+/// copy of the json.
+///
+/// The `index` of the value is an offset into json only when json is valid
+/// UTF-8. It's `None` when any bytes were replaced, because the offsets of
+/// the replaced json no longer line up with the input.
+pub fn get_bytes_lossy(json: &[u8], path: &str) -> Value<'static> {
+    let json = String::from_utf8_lossy(json);
+    let mut res = json_into_owned(get(&json, path));
+    if let Cow::Owned(_) = json {
+        res.index = None;
+    }
+    res
 }
 
 fn json_into_owned<'a>(json: Value) -> Value<'a> {
     Value {
         slice: "",
-        owned: if json.slice.len() > 0 {
+        owned: if !json.slice.is_empty() {
             json.slice.to_owned()
         } else {
             json.owned
         },
-        uescstr: json.uescstr,
+        uescstr: Cow::Owned(json.uescstr.into_owned()),
         info: json.info,
         index: json.index,
+        ordinal: json.ordinal,
+        siblings: json.siblings,
+        member: Cow::Owned(json.member.into_owned()),
     }
 }
 
 /// Parse the json and return it as a value.
 ///
+/// Any kind of value may be parsed, including a scalar such as `3.14`,
+/// `"hello"`, or `true`. Leading whitespace is skipped and anything that
+/// follows a scalar is ignored.
+///
 /// This function expects that the json is valid, and does not validate.
 /// Invalid json will not panic, but it may return back unexpected results.
 /// If you are consuming JSON from an unpredictable source then you may want to
@@ -1122,11 +2790,11 @@ pub fn parse<'a>(json: &'a str) -> Value<'a> {
         match json[i] {
             b'{' => return json_from_slice(&json[i..], Some(i), INFO_OBJECT | INFO_FOG),
             b'[' => return json_from_slice(&json[i..], Some(i), INFO_ARRAY | INFO_FOG),
-            b't' | b'f' | b'n' | b'"' | b'0' | b'1' | b'2' => {}
-            b'3' | b'4' | b'5' | b'6' | b'7' | b'8' | b'9' | b'-' => {}
+            b't' | b'f' | b'n' | b'"' | b'-' | b'0'..=b'9' => {
+                return proc_value(json, i, Path::default(), true).0;
+            }
             _ => break,
         }
-        return proc_value(json, i, Path::default(), true).0;
     }
-    return Value::default();
+    Value::default()
 }
@@ -7,17 +7,22 @@
 
 use super::path::Path;
 use super::pretty;
+use super::util::{base64_decode, base64_encode, escape, extend_json_string, fmt_usize, trim};
 use super::valid::valid;
 use super::*;
 use std::collections::HashMap;
 use std::str;
 
-pub fn exec<'a>(json: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
+// exec executes the modifier at path on json. The root is the original json
+// document that was passed to `get`.
+pub fn exec<'a>(json: &'a [u8], root: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
     let (name, json_str, arg);
+    // bare modifiers, such as `length`, don't have the '@' prefix
+    let s = if path.comp[0] == b'@' { 1 } else { 0 };
     // SAFETY: all json and path parts are prechecked utf8
     unsafe {
         if path.marg == 0 {
-            name = str::from_utf8_unchecked(&path.comp[1..]);
+            name = str::from_utf8_unchecked(&path.comp[s..]);
             json_str = str::from_utf8_unchecked(json);
             arg = "";
         } else {
@@ -27,17 +32,46 @@ pub fn exec<'a>(json: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
         }
     };
     let json = json_str;
+    if trim(json.as_bytes()).is_empty() && !matches!(name, "default" | "coalesce" | "root") {
+        // A missing value stays missing. Only the modifiers that are made for
+        // handling a missing value, and @root which ignores its input, run.
+        return (Value::default(), path);
+    }
     let json = match name {
         "this" => mod_this(json, arg),
+        "root" => mod_this(tostr(root), arg),
         "reverse" => mod_reverse(json, arg),
         "ugly" => mod_ugly(json, arg),
         "pretty" => mod_pretty(json, arg),
         "valid" => mod_valid(json, arg),
         "flatten" => mod_flatten(json, arg),
+        "concat" => mod_concat(json, arg),
+        "explode" => mod_explode(json, arg),
+        "implode" => mod_implode(json, arg),
+        "default" => mod_default(json, arg),
+        "coalesce" => mod_coalesce(json, arg),
         "join" => mod_join(json, arg),
+        "keys" => mod_keys(json, arg),
+        "values" => mod_values(json, arg),
+        "length" => mod_length(json, arg),
+        "compact" => mod_compact(json, arg),
+        "spread" => mod_spread(json, arg),
+        "base64d" => mod_base64d(json, arg),
+        "base64e" => mod_base64e(json, arg),
+        "pick" => mod_pick(json, arg),
+        "omit" => mod_omit(json, arg),
+        "filterkeys" => mod_filterkeys(json, arg),
+        "round" => mod_round(json, arg),
+        "limit" => mod_limit(json, arg),
         _ => String::new(),
     };
-    (json_into_owned(parse(&json)), path)
+    let mut res = json_into_owned(parse(&json));
+    // The output is a new document, so the positions of it and its children
+    // only carry over to the input when the output is the same text.
+    if json != json_str {
+        res.index = None;
+    }
+    (res, path)
 }
 
 fn mod_this(json: &str, _: &str) -> String {
@@ -180,6 +214,409 @@ fn mod_join(json: &str, arg: &str) -> String {
     unsafe { String::from_utf8_unchecked(out) }
 }
 
+// @pick returns an object containing only the fields in the {"fields":[...]}
+// arg. Each field is a path, which is also used as the key for its value.
+//   {"a":1,"b":2,"c":{"d":3}} + {"fields":["a","c.d"]} -> {"a":1,"c.d":3}
+// Fields that do not exist are skipped. Nothing is returned when the json is
+// not an object.
+fn mod_pick(json: &str, arg: &str) -> String {
+    if parse(json).kind() != Kind::Object {
+        return String::new();
+    }
+    let mut out = Vec::new();
+    out.push(b'{');
+    let mut idx = 0;
+    get(arg, "fields").each(|_, field| {
+        let value = get(json, field.str());
+        if value.exists() {
+            if idx > 0 {
+                out.push(b',');
+            }
+            extend_json_string(&mut out, field.str().as_bytes());
+            out.push(b':');
+            out.extend(value.json().as_bytes());
+            idx += 1;
+        }
+        true
+    });
+    out.push(b'}');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @omit returns the object without the fields in the {"fields":[...]} arg.
+//   {"a":1,"b":2,"c":3} + {"fields":["a","c"]} -> {"b":2}
+// A field may be a dotted path for removing a nested field.
+//   {"a":1,"b":{"c":2,"d":3}} + {"fields":["b.c"]} -> {"a":1,"b":{"d":3}}
+// Json that is not an object is returned unchanged.
+//   [1,2] + {"fields":["0"]} -> [1,2]
+fn mod_omit(json: &str, arg: &str) -> String {
+    let fields = get(arg, "fields");
+    let mut omit = Vec::new();
+    fields.each(|_, field| {
+        omit.push(field.str().to_owned());
+        true
+    });
+    let omit: Vec<&str> = omit.iter().map(|field| field.as_str()).collect();
+    omit_fields(json, &omit)
+}
+
+// omit_fields returns the object without the fields. A nested value that is
+// not an object, such as `b` for the `b.c` field of {"b":1}, is kept as-is.
+fn omit_fields(json: &str, fields: &[&str]) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Object {
+        return json.to_owned();
+    }
+    let mut out = Vec::new();
+    out.push(b'{');
+    let mut idx = 0;
+    res.each(|key, value| {
+        let mut nested = Vec::new();
+        for field in fields {
+            let (name, rest) = split_field(field);
+            if name == key.str() {
+                match rest {
+                    Some(rest) => nested.push(rest),
+                    None => return true,
+                }
+            }
+        }
+        if idx > 0 {
+            out.push(b',');
+        }
+        out.extend(key.json().as_bytes());
+        out.push(b':');
+        if nested.is_empty() {
+            out.extend(value.json().as_bytes());
+        } else {
+            out.extend(omit_fields(value.json(), &nested).as_bytes());
+        }
+        idx += 1;
+        true
+    });
+    out.push(b'}');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @filterkeys returns the object with only the members whose key matches
+// the wildcard pattern in the arg.
+//   {"user_id":1,"name":"Tom","user_age":37} + "user_*" -> {"user_id":1,"user_age":37}
+// Nothing is returned when the json is not an object.
+fn mod_filterkeys(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Object {
+        return String::new();
+    }
+    let pattern = parse(arg);
+    let pattern = if pattern.kind() == Kind::String {
+        pattern.str()
+    } else {
+        arg
+    };
+    let mut out = Vec::new();
+    out.push(b'{');
+    let mut idx = 0;
+    res.each(|key, value| {
+        if pmatch(pattern, key.str()) {
+            if idx > 0 {
+                out.push(b',');
+            }
+            out.extend(key.json().as_bytes());
+            out.push(b':');
+            out.extend(value.json().as_bytes());
+            idx += 1;
+        }
+        true
+    });
+    out.push(b'}');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @round rounds every number in the json to the number of decimal places in
+// the arg, which is zero when there's no arg.
+//   {"pi":3.14159,"n":[2.5,10]} + 2 -> {"pi":3.14,"n":[2.5,10]}
+//   {"pi":3.14159,"n":[2.5,10]} -> {"pi":3,"n":[3,10]}
+// Integers, and numbers too large to have a fraction as a f64, are passed
+// through untouched, as is the json when the arg is more than 15 places.
+fn mod_round(json: &str, arg: &str) -> String {
+    let places = if arg.is_empty() { 0 } else { parse(arg).i64() };
+    if !(0..=15).contains(&places) {
+        return json.to_owned();
+    }
+    let scale = 10f64.powi(places as i32);
+    let json = json.as_bytes();
+    let mut out = Vec::with_capacity(json.len());
+    let mut i = 0;
+    while i < json.len() {
+        match json[i] {
+            b'"' => {
+                let s = i;
+                i += 1;
+                while i < json.len() {
+                    if json[i] == b'\\' {
+                        i += 1;
+                    } else if json[i] == b'"' {
+                        break;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(json.len());
+                out.extend(&json[s..i]);
+            }
+            b'-' | b'0'..=b'9' => {
+                let (num, info, next_i) = scan_number(json, i);
+                let f: f64 = tostr(num).parse().unwrap_or(0.0);
+                if info & (INFO_DOT | INFO_E) == 0 || f.abs() >= 9007199254740992.0 {
+                    out.extend(num);
+                } else {
+                    let f = (f * scale).round() / scale;
+                    let s = format!("{:.*}", places as usize, f);
+                    let s = if s.contains('.') {
+                        s.trim_end_matches('0').trim_end_matches('.')
+                    } else {
+                        &s
+                    };
+                    out.extend(if s == "-0" { "0" } else { s }.as_bytes());
+                }
+                i = next_i;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    // SAFETY: only complete utf8 sequences and ascii parts were copied.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @limit returns the first N elements of an array, where N is the arg.
+//   [1,2,3,4] + 2 -> [1,2]
+// A zero or negative N returns an empty array. The json is returned as is
+// when it's not an array or there's no arg.
+fn mod_limit(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Array || arg.is_empty() {
+        return json.to_owned();
+    }
+    let limit = parse(arg).i64().max(0);
+    let mut out = Vec::new();
+    out.push(b'[');
+    let mut idx = 0;
+    if limit > 0 {
+        res.each(|_, value| {
+            if idx > 0 {
+                out.push(b',');
+            }
+            out.extend(value.json().as_bytes());
+            idx += 1;
+            idx < limit
+        });
+    }
+    out.push(b']');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// split_field splits a dotted field into its first key and the remaining
+// path, if any. A dot may be escaped with a '\'.
+fn split_field(field: &str) -> (String, Option<&str>) {
+    let mut name = String::new();
+    let mut chars = field.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, c)) = chars.next() {
+                    name.push(c);
+                }
+            }
+            '.' => return (name, Some(&field[i + 1..])),
+            _ => name.push(c),
+        }
+    }
+    (name, None)
+}
+
+// @explode splits a string on the separator in the arg, which is either a
+// string or the {"sep":","} form.
+//   "a,b,c" + "," -> ["a","b","c"]
+// An empty string becomes an empty array, and an empty separator returns the
+// whole string as the only element. The original json is returned when the
+// json is not a string.
+fn mod_explode(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::String {
+        return json.to_owned();
+    }
+    let sep = separator(arg);
+    let s = res.str();
+    let mut out = Vec::new();
+    out.push(b'[');
+    if !s.is_empty() {
+        if sep.is_empty() {
+            extend_json_string(&mut out, s.as_bytes());
+        } else {
+            for (idx, part) in s.split(sep.as_str()).enumerate() {
+                if idx > 0 {
+                    out.push(b',');
+                }
+                extend_json_string(&mut out, part.as_bytes());
+            }
+        }
+    }
+    out.push(b']');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @implode joins the elements of an array into a string, using the separator
+// in the arg, which is either a string or the {"sep":","} form.
+//   ["a","b","c"] + "," -> "a,b,c"
+// Each element is converted to text in the same way as Value::str, where
+// objects and arrays are their raw json and null is empty. The original json
+// is returned when the json is not an array.
+fn mod_implode(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Array {
+        return json.to_owned();
+    }
+    let sep = separator(arg);
+    let mut out = String::new();
+    let mut idx = 0;
+    res.each(|_, value| {
+        if idx > 0 {
+            out.push_str(&sep);
+        }
+        out.push_str(value.str());
+        idx += 1;
+        true
+    });
+    escape(&out)
+}
+
+// separator returns the separator for @explode and @implode from the arg.
+fn separator(arg: &str) -> String {
+    let sep = parse(arg);
+    if sep.kind() == Kind::Object {
+        get(arg, "sep").str().to_owned()
+    } else if sep.kind() == Kind::String {
+        sep.str().to_owned()
+    } else {
+        arg.to_owned()
+    }
+}
+
+// @default returns the json in the arg when the json is missing or null.
+//   missing + 1 -> 1
+//   null + "none" -> "none"
+//   2 + 1 -> 2
+fn mod_default(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if !res.exists() || res.kind() == Kind::Null {
+        arg.to_owned()
+    } else {
+        json.to_owned()
+    }
+}
+
+// @coalesce returns the first element of an array that isn't null, or the
+// json in the arg when there is no such element.
+//   [null,2,3] -> 2
+//   [null,null] + 0 -> 0
+// Any other json is handled the same as @default.
+fn mod_coalesce(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Array {
+        return mod_default(json, arg);
+    }
+    let mut out = None;
+    res.each(|_, value| {
+        if value.kind() == Kind::Null {
+            return true;
+        }
+        out = Some(value.json().to_owned());
+        false
+    });
+    out.unwrap_or_else(|| arg.to_owned())
+}
+
+// @base64d decodes the base64 contents of a string into a new string.
+//   "aGVsbG8=" -> "hello"
+// Nothing is returned when the json is not a string, or when the contents are
+// not valid base64 or do not decode to utf8.
+fn mod_base64d(json: &str, _: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::String {
+        return String::new();
+    }
+    match base64_decode(res.str()).map(String::from_utf8) {
+        Some(Ok(decoded)) => escape(&decoded),
+        _ => String::new(),
+    }
+}
+
+// @base64e encodes a string into base64. Other kinds of json are encoded
+// using their raw json.
+//   "hello" -> "aGVsbG8="
+//   {"a":1} -> "eyJhIjoxfQ=="
+fn mod_base64e(json: &str, _: &str) -> String {
+    let res = parse(json);
+    if !res.exists() {
+        return String::new();
+    }
+    let src = if res.kind() == Kind::String {
+        res.str()
+    } else {
+        res.json()
+    };
+    escape(&base64_encode(src.as_bytes()))
+}
+
+// @spread an array of objects into a single object, where later keys win.
+//   [{"a":1,"b":2},{"a":3}] -> {"b":2,"a":3}
+// Unlike @join, which keeps each key at the position where it first appeared,
+// a key that appears again is moved to the position of its last appearance.
+// Elements that are not objects are ignored, and the original json is
+// returned when the json is not an array.
+fn mod_spread(json: &str, _: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Array {
+        return json.to_owned();
+    }
+    let mut pairs: Vec<Option<(Vec<u8>, Vec<u8>)>> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    res.each(|_, value| {
+        if value.kind() != Kind::Object {
+            return true;
+        }
+        value.each(|key, value| {
+            if let Some(i) = index.insert(key.str().to_owned(), pairs.len()) {
+                pairs[i] = None;
+            }
+            let key = key.json().as_bytes().to_owned();
+            pairs.push(Some((key, value.json().as_bytes().to_owned())));
+            true
+        });
+        true
+    });
+    let mut out = Vec::new();
+    out.push(b'{');
+    for (key, value) in pairs.iter().flatten() {
+        if out.len() > 1 {
+            out.push(b',');
+        }
+        out.extend(key);
+        out.push(b':');
+        out.extend(value);
+    }
+    out.push(b'}');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
 // @flatten an array with child arrays.
 //   [1,[2],[3,4],[5,[6,7]]] -> [1,2,3,4,5,[6,7]]
 // The {"deep":true} arg can be provide for deep flattening.
@@ -219,6 +656,167 @@ fn mod_flatten(json: &str, arg: &str) -> String {
     unsafe { String::from_utf8_unchecked(out) }
 }
 
+// @concat concatenates the arrays in an array, one level deep.
+//   [[1,2],[3],"x",[4]] -> [1,2,3,4]
+// Elements that are not arrays are dropped, unless the {"keep":true} arg is
+// provided, in which case they are kept as-is.
+//   [[1,2],[3],"x",[4]] -> [1,2,3,"x",4]
+// The original json is returned when the json is not an array.
+fn mod_concat(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Array {
+        return json.to_owned();
+    }
+    let keep = get(arg, "keep").bool();
+    let mut out = Vec::new();
+    out.push(b'[');
+    let mut idx = 0;
+    res.each(|_, value| {
+        let raw = if value.kind() == Kind::Array {
+            trim(unwrap(value.json().as_bytes()))
+        } else if keep {
+            value.json().as_bytes()
+        } else {
+            return true;
+        };
+        if !raw.is_empty() {
+            if idx > 0 {
+                out.push(b',');
+            }
+            out.extend(raw);
+            idx += 1;
+        }
+        true
+    });
+    out.push(b']');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @keys returns an array of the keys for an object.
+//   {"first":"Tom","last":"Anderson"} -> ["first","last"]
+// Each element of an array has no key and is returned as null.
+fn mod_keys(json: &str, _: &str) -> String {
+    let res = parse(json);
+    let obj = res.kind() == Kind::Object;
+    let mut out = Vec::new();
+    out.push(b'[');
+    let mut idx = 0;
+    res.each(|key, _| {
+        if idx > 0 {
+            out.push(b',');
+        }
+        if obj {
+            out.extend(key.json().as_bytes());
+        } else {
+            out.extend(b"null");
+        }
+        idx += 1;
+        true
+    });
+    out.push(b']');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @values returns an array of the values for an object.
+//   {"first":"Tom","last":"Anderson"} -> ["Tom","Anderson"]
+// The original json is returned when the json is an array.
+fn mod_values(json: &str, _: &str) -> String {
+    let res = parse(json);
+    if res.kind() == Kind::Array {
+        return json.to_owned();
+    }
+    let mut out = Vec::new();
+    out.push(b'[');
+    let mut idx = 0;
+    res.each(|_, value| {
+        if idx > 0 {
+            out.push(b',');
+        }
+        out.extend(value.json().as_bytes());
+        idx += 1;
+        true
+    });
+    out.push(b']');
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// @length returns the number of members in an object or array, or the number
+// of characters in a string. Nothing is returned for other kinds.
+fn mod_length(json: &str, _: &str) -> String {
+    let res = parse(json);
+    match res.kind() {
+        Kind::Object | Kind::Array => {
+            let mut count = 0;
+            res.each(|_, _| {
+                count += 1;
+                true
+            });
+            fmt_usize(count)
+        }
+        Kind::String => fmt_usize(res.str().chars().count()),
+        _ => String::new(),
+    }
+}
+
+// @compact removes null values from an object or array.
+//   {"a":1,"b":null,"c":[1,null]} -> {"a":1,"c":[1,null]}
+// The {"deep":true} arg can be provided for removing nested null values.
+//   {"a":1,"b":null,"c":[1,null]} -> {"a":1,"c":[1]}
+// The {"removeEmpty":true} arg also removes objects and arrays that are empty
+// after compacting. The outermost object or array is never removed.
+//   {"a":1,"b":{"c":null}} -> {"a":1}
+// The original json is returned when the json is not an object or array.
+fn mod_compact(json: &str, arg: &str) -> String {
+    let res = parse(json);
+    if res.kind() != Kind::Object && res.kind() != Kind::Array {
+        return json.to_owned();
+    }
+    let deep = get(arg, "deep").bool();
+    let remove_empty = get(arg, "removeEmpty").bool();
+    compact(json, deep, remove_empty)
+}
+
+fn compact(json: &str, deep: bool, remove_empty: bool) -> String {
+    let res = parse(json);
+    let obj = res.kind() == Kind::Object;
+    let mut out = Vec::new();
+    out.push(if obj { b'{' } else { b'[' });
+    let mut idx = 0;
+    res.each(|key, value| {
+        let kind = value.kind();
+        if kind == Kind::Null {
+            return true;
+        }
+        let container = kind == Kind::Object || kind == Kind::Array;
+        let compacted;
+        let raw = if deep && container {
+            compacted = compact(value.json(), deep, remove_empty);
+            compacted.as_bytes()
+        } else {
+            value.json().as_bytes()
+        };
+        if remove_empty && container && unwrap(raw).iter().all(|c| *c <= b' ') {
+            return true;
+        }
+        if idx > 0 {
+            out.push(b',');
+        }
+        if obj {
+            out.extend(key.json().as_bytes());
+            out.push(b':');
+        }
+        out.extend(raw);
+        idx += 1;
+        true
+    });
+    out.push(if obj { b'}' } else { b']' });
+    // SAFETY: buffer was constructed from known utf8 parts.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
 fn unwrap<'a>(mut json: &'a [u8]) -> &'a [u8] {
     while json.len() > 0 && json[0] <= b' ' {
         json = &json[1..];
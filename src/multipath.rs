@@ -95,24 +95,25 @@ fn each_comp(path: &[u8], mut iter: impl FnMut(&[u8], &[u8])) {
     }
 }
 
-pub fn exec<'a>(json: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
+pub fn exec<'a>(json: &'a [u8], root: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
     // it's expected that path.comp starts with a '[' or '{'
     if path.comp[0] == b'[' {
-        exec_arr(json, path)
+        exec_arr(json, root, path)
     } else {
-        exec_obj(json, path)
+        exec_obj(json, root, path)
     }
 }
 
-fn exec_arr<'a>(json: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
+fn exec_arr<'a>(json: &'a [u8], root: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
     if path.comp[0] == b'[' && path.comp[path.comp.len() - 1] != b']' {
         return (Value::default(), Path::default());
     }
     let mut out = Vec::new();
     out.push(b'[');
     let mut index = 0;
+    let depth = path.depth;
     each_comp(path.comp, |_, path| {
-        let res = get(tostr(json), tostr(path));
+        let res = get_with_root(tostr(json), tostr(path), tostr(root), false, depth, false);
         if res.exists() {
             if index > 0 {
                 out.push(b',');
@@ -126,15 +127,16 @@ fn exec_arr<'a>(json: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
     (json_from_owned(json, None, INFO_ARRAY), path)
 }
 
-fn exec_obj<'a>(json: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
+fn exec_obj<'a>(json: &'a [u8], root: &'a [u8], path: Path<'a>) -> (Value<'a>, Path<'a>) {
     if path.comp[0] == b'{' && path.comp[path.comp.len() - 1] != b'}' {
         return (Value::default(), Path::default());
     }
     let mut out = Vec::new();
     out.push(b'{');
     let mut index = 0;
+    let depth = path.depth;
     each_comp(path.comp, |key, path| {
-        let res = get(tostr(json), tostr(path));
+        let res = get_with_root(tostr(json), tostr(path), tostr(root), false, depth, false);
         if res.exists() {
             if index > 0 {
                 out.push(b',');
@@ -5,9 +5,83 @@
 // Bit flags passed to the "info" parameter of the iter function which
 // provides additional information about the data
 
-use super::util::{tostr, trim};
+use super::util::{exceeds_depth, percent_decode, tostr, trim};
 use super::{proc_value, scan_squash};
 
+// Function names that may follow a pipe without the '@' prefix, such as
+// `friends|length`. Each maps to the modifier of the same name.
+const BARE_MODIFIERS: [&str; 3] = ["length", "keys", "values"];
+
+// with_separator converts a path that uses sep, rather than a dot, to separate
+// its components into a standard path. Dots in the path become escaped dots,
+// and an escaped sep becomes a plain sep character. Queries, multipaths, and
+// modifier arguments are copied as-is.
+pub fn with_separator(path: &str, sep: char) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    let mut depth = 0;
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(c) if c == sep && depth == 0 && !quoted => out.push(c),
+                Some(c) => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                None => out.push('\\'),
+            }
+        } else if quoted {
+            quoted = c != '"';
+            out.push(c);
+        } else if c == '"' {
+            quoted = true;
+            out.push(c);
+        } else if c == '(' || c == '[' || c == '{' {
+            depth += 1;
+            out.push(c);
+        } else if depth > 0 {
+            if c == ')' || c == ']' || c == '}' {
+                depth -= 1;
+            }
+            out.push(c);
+        } else if c == sep {
+            out.push('.');
+        } else if c == '.' {
+            out.push_str("\\.");
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// from_urlpath converts a dotted path with percent-encoded segments, such as
+// `first%20name.0`, into a standard path. The path is split on its dots
+// before each segment is decoded, so that an encoded dot (`%2E`) is part of
+// a key rather than a separator. Each decoded segment is a literal key.
+pub fn from_urlpath(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for (i, segment) in path.split('.').enumerate() {
+        if i > 0 {
+            out.push('.');
+        }
+        push_path_key(&mut out, &percent_decode(segment));
+    }
+    out
+}
+
+// push_path_key appends the key to the path, escaping any characters that
+// have a special meaning in a path.
+pub fn push_path_key(path: &mut String, key: &str) {
+    for c in key.chars() {
+        if !(c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == ':' || !c.is_ascii()) {
+            path.push('\\');
+        }
+        path.push(c);
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Path<'a> {
     pub comp: &'a [u8],
@@ -16,6 +90,13 @@ pub struct Path<'a> {
     pub sep: u8,
     pub marg: usize,
     pub extra: &'a [u8],
+    // meta is set by get_with_meta, and asks the last component to count the
+    // elements of its array into siblings.
+    pub meta: bool,
+    pub siblings: Option<usize>,
+    // depth is the number of objects and arrays that may still be entered,
+    // which is only limited by get_with_depth.
+    pub depth: Option<usize>,
 }
 
 impl<'a> Path<'a> {
@@ -30,12 +111,47 @@ impl<'a> Path<'a> {
             pat: false,
             sep: 0,
             marg: 0,
+            meta: false,
+            siblings: None,
+            depth: None,
         };
         path_next(&path)
     }
+    // enter returns the path to use inside of an object or array, which is
+    // None when the depth limit has been reached.
+    pub fn enter(&self) -> Option<Path<'a>> {
+        let mut path = *self;
+        match path.depth {
+            Some(0) => return None,
+            Some(depth) => path.depth = Some(depth - 1),
+            None => {}
+        }
+        Some(path)
+    }
+    // exceeds_depth returns true if the json is nested deeper than the depth
+    // limit, which is checked before the json is handed to a modifier.
+    pub fn exceeds_depth(&self, json: &[u8]) -> bool {
+        match self.depth {
+            Some(depth) => exceeds_depth(json, depth),
+            None => false,
+        }
+    }
     pub fn is_modifier(&self) -> bool {
         self.comp.len() > 0 && self.comp[0] == b'@'
     }
+    // is_bare_modifier returns true if the component is one of the function
+    // names that may be used without the '@' prefix. It's up to the caller to
+    // ensure that the component directly follows a pipe.
+    pub fn is_bare_modifier(&self) -> bool {
+        BARE_MODIFIERS.iter().any(|name| name.as_bytes() == self.comp)
+    }
+    // is_wildcard_all returns true if the component is a key pattern that
+    // ends with a `#`, such as `child*#`, which collects the matching keys of
+    // an object rather than only the first.
+    pub fn is_wildcard_all(&self) -> bool {
+        let n = self.comp.len();
+        self.pat && n > 1 && self.comp[n - 1] == b'#' && self.comp[n - 2] != b'\\'
+    }
     pub fn is_multipath(&self) -> bool {
         self.comp.len() > 0 && (self.comp[0] == b'{' || self.comp[0] == b'[')
     }
@@ -73,102 +189,167 @@ impl<'a> Path<'a> {
     }
 
     // -> lh, op, rh
-    pub fn query_parts(&self) -> (&'a str, &'a str, &'a str) {
-        let mut lh = "";
-        let mut op = "";
-        let mut rh = "";
-        'bad: loop {
-            // take the inner contents of the query
-            let mut query = self.comp;
-            if query.len() < 2 || query[0] != b'#' || query[1] != b'(' {
-                break 'bad;
-            } else if query[query.len() - 1] == b'#' {
-                if query[query.len() - 2] != b')' {
-                    break 'bad;
-                } else {
-                    query = &query[2..query.len() - 2];
-                }
-            } else if query[query.len() - 1] != b')' {
-                break 'bad;
+    // query_conditions returns the conditions of a query, such as
+    // `#(age>40 && last=="Murphy" || first=="Jane")`, as groups that are
+    // joined by `||`, where each group is the conditions joined by `&&`.
+    // A query with a single condition returns one group with one condition.
+    pub fn query_conditions(&self) -> Vec<Vec<(&'a str, &'a str, &'a str)>> {
+        let query = match self.query_inner() {
+            Some(query) => query,
+            None => return vec![vec![("", "", "")]],
+        };
+        split_query(query, b"||")
+            .into_iter()
+            .map(|group| {
+                split_query(group, b"&&")
+                    .into_iter()
+                    .map(condition_parts)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // query_inner returns the contents of the query, without the wrapping
+    // `#(` and `)` or `)#`.
+    fn query_inner(&self) -> Option<&'a [u8]> {
+        let query = self.comp;
+        if query.len() < 2 || query[0] != b'#' || query[1] != b'(' {
+            None
+        } else if query[query.len() - 1] == b'#' {
+            if query[query.len() - 2] != b')' {
+                None
             } else {
-                query = &query[2..query.len() - 1];
+                Some(&query[2..query.len() - 2])
             }
+        } else if query[query.len() - 1] != b')' {
+            None
+        } else {
+            Some(&query[2..query.len() - 1])
+        }
+    }
+}
 
-            // trim the query
-            query = trim(query);
+// condition_parts returns the left side, operator, and right side of a single
+// query condition, such as `last=="Murphy"`.
+fn condition_parts(query: &[u8]) -> (&str, &str, &str) {
+    // trim the query
+    let query = trim(query);
 
-            // locate the operator
-            let mut depth = 0;
-            let mut i = 0;
-            while i < query.len() {
-                if query[i] == b'\\' {
-                    if i + 1 == query.len() {
-                        break;
-                    }
-                    i += 2;
-                    continue;
+    // locate the operator
+    let mut depth = 0;
+    let mut i = 0;
+    while i < query.len() {
+        if query[i] == b'\\' {
+            if i + 1 == query.len() {
+                break;
+            }
+            i += 2;
+            continue;
+        }
+        if query[i] == b'(' {
+            depth += 1;
+            i += 1;
+            continue;
+        } else if query[i] == b')' {
+            depth -= 1;
+            i += 1;
+            continue;
+        } else if depth > 0 {
+            i += 1;
+            continue;
+        }
+
+        let mut found = true;
+        let mut s = 0;
+        let mut e = 0;
+        match query[i] {
+            b'(' => {
+                depth += 1;
+            }
+            b'%' => {
+                s = i;
+                e = i + 1;
+            }
+            b'~' if query[i + 1..].starts_with(b"==") => {
+                s = i;
+                e = i + 3;
+            }
+            b'~' => {
+                s = i;
+                e = i + 1;
+            }
+            b'!' => {
+                if i + 1 < query.len()
+                    && (query[i + 1] == b'=' || query[i + 1] == b'%' || query[i + 1] == b'~')
+                {
+                    s = i;
+                    e = i + 2;
+                } else {
+                    s = i;
+                    e = i + 1;
                 }
-                if query[i] == b'(' {
-                    depth += 1;
-                    i += 1;
-                    continue;
-                } else if query[i] == b')' {
-                    depth -= 1;
-                    i += 1;
-                    continue;
-                } else if depth > 0 {
-                    i += 1;
-                    continue;
+                if query[s..e] == *b"!=" && e < query.len() && query[e] == b'#' {
+                    // numeric comparison
+                    e += 1;
                 }
-
-                let mut found = true;
-                let mut s = 0;
-                let mut e = 0;
-                match query[i] {
-                    b'(' => {
-                        depth += 1;
-                    }
-                    b'%' => {
-                        s = i;
-                        e = i + 1;
-                    }
-                    b'!' => {
-                        if i + 1 < query.len() && (query[i + 1] == b'=' || query[i + 1] == b'%') {
-                            s = i;
-                            e = i + 2;
-                        } else {
-                            s = i;
-                            e = i + 1;
-                        }
-                    }
-                    b'=' | b'<' | b'>' => {
-                        if i + 1 < query.len() && query[i + 1] == b'=' {
-                            s = i;
-                            e = i + 2;
-                        } else {
-                            s = i;
-                            e = i + 1;
-                        }
-                    }
-                    _ => {
-                        found = false;
-                    }
+            }
+            b'=' | b'<' | b'>' => {
+                if i + 1 < query.len() && query[i + 1] == b'=' {
+                    s = i;
+                    e = i + 2;
+                } else {
+                    s = i;
+                    e = i + 1;
                 }
-                if found {
-                    lh = tostr(trim(&query[..s]));
-                    op = tostr(trim(&query[s..e]));
-                    rh = tostr(trim(&query[e..]));
-                    if op == "==" {
-                        op = &op[0..1];
-                    }
-                    return (lh, op, rh);
+                if e < query.len() && query[e] == b'#' {
+                    // numeric comparison, such as `>#`
+                    e += 1;
                 }
-                i += 1;
             }
-            return (tostr(query), "", "");
+            _ => {
+                found = false;
+            }
+        }
+        if found {
+            let lh = tostr(trim(&query[..s]));
+            let mut op = tostr(trim(&query[s..e]));
+            let rh = tostr(trim(&query[e..]));
+            if op == "==" || op == "==#" {
+                op = &op[1..];
+            }
+            return (lh, op, rh);
+        }
+        i += 1;
+    }
+    (tostr(query), "", "")
+}
+
+// split_query splits the query on each occurrence of the operator, such as
+// `&&`, that is not within a string or parentheses.
+fn split_query<'a>(query: &'a [u8], op: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut s = 0;
+    let mut i = 0;
+    while i < query.len() {
+        match query[i] {
+            b'\\' => i += 1,
+            b'"' => quoted = !quoted,
+            b'(' if !quoted => depth += 1,
+            b')' if !quoted => depth -= 1,
+            _ if !quoted && depth == 0 && query[i..].starts_with(op) => {
+                parts.push(&query[s..i]);
+                i += op.len();
+                s = i;
+                continue;
+            }
+            _ => {}
         }
-        (lh, op, rh)
+        i += 1;
     }
+    parts.push(&query[s..]);
+    parts
 }
 
 impl<'a> Default for Path<'a> {
@@ -232,6 +413,9 @@ fn path_next_query<'a>(path: &Path<'a>) -> Path<'a> {
         sep: sep,
         marg: 0,
         extra: extra,
+        meta: path.meta,
+        siblings: None,
+        depth: path.depth,
     };
     if path.comp[path.comp.len() - 1] == b'#' {
         if path.comp[path.comp.len() - 2] != b')' {
@@ -264,6 +448,9 @@ fn path_next_multipath<'a>(path: &Path<'a>) -> Path<'a> {
         sep: sep,
         marg: 0,
         extra: &path.extra[s..],
+        meta: path.meta,
+        siblings: None,
+        depth: path.depth,
     }
 }
 
@@ -340,6 +527,9 @@ fn path_next<'a>(path: &Path<'a>) -> Path<'a> {
         sep: sep,
         marg: marg,
         extra: &path.extra[i..],
+        meta: path.meta,
+        siblings: None,
+        depth: path.depth,
     }
 }
 
@@ -386,15 +576,67 @@ mod test {
     #[test]
     fn query() {
         let path = Path::new("#(hello=world)");
-        let (lh, op, rh) = path.query_parts();
-        assert_eq!("hello", lh);
-        assert_eq!("=", op);
-        assert_eq!("world", rh);
+        assert!(path.query_conditions() == [[("hello", "=", "world")]]);
+
+        // a count in the left side is part of the path
+        for (query, lh, rh) in [
+            ("#(nets.#>2)", "nets.#", "2"),
+            ("#(nets.#>2)#", "nets.#", "2"),
+            ("#(#>2)#", "#", "2"),
+            ("#(a.#(b>1)#.#>2)", "a.#(b>1)#.#", "2"),
+        ] {
+            let path = Path::new(query);
+            assert!(path.query_conditions() == [[(lh, ">", rh)]]);
+        }
+
+        let path = Path::new("#(a>1 && b==\"x && y || z\" || c.#(d==1&&e==2) && f)#");
+        assert!(
+            path.query_conditions()
+                == [
+                    vec![("a", ">", "1"), ("b", "=", "\"x && y || z\"")],
+                    vec![("c.#(d==1&&e==2)", "", ""), ("f", "", "")],
+                ]
+        );
+
+        let path = Path::new("#(a>#1 && b==#2 || c!=#3 && d<=~4)");
+        assert!(
+            path.query_conditions()
+                == [
+                    vec![("a", ">#", "1"), ("b", "=#", "2")],
+                    vec![("c", "!=#", "3"), ("d", "<=", "~4")],
+                ]
+        );
 
         let path = Path::new("");
-        let (lh, op, rh) = path.query_parts();
-        assert_eq!("", lh);
-        assert_eq!("", op);
-        assert_eq!("", rh);
+        assert!(path.query_conditions() == [[("", "", "")]]);
+    }
+    #[test]
+    fn bare_modifier() {
+        let path = Path::new("friends|length");
+        assert!(!path.is_bare_modifier());
+        assert!(path.next().is_bare_modifier());
+        assert!(!Path::new("lengths").is_bare_modifier());
+        assert!(!Path::new("@length").is_bare_modifier());
+    }
+
+    #[test]
+    fn separator() {
+        assert_eq!(with_separator("fav.movie", '/'), "fav\\.movie");
+        assert_eq!(with_separator("a/b.c/0", '/'), "a.b\\.c.0");
+        assert_eq!(with_separator("a\\/b/c", '/'), "a/b.c");
+        assert_eq!(with_separator("a\\*/b", '/'), "a\\*.b");
+        assert_eq!(with_separator("f/#(a.b==\"x/y\")#/c|d", '/'), "f.#(a.b==\"x/y\")#.c|d");
+        assert_eq!(with_separator("[a.b,c]/0", '/'), "[a.b,c].0");
+        assert_eq!(with_separator("a:b.c", ':'), "a.b\\.c");
+    }
+
+    #[test]
+    fn wildcard_all() {
+        assert!(Path::new("*#").is_wildcard_all());
+        assert!(Path::new("child*#").is_wildcard_all());
+        assert!(Path::new("friends.*#.first").next().is_wildcard_all());
+        assert!(!Path::new("*").is_wildcard_all());
+        assert!(!Path::new("*\\#").is_wildcard_all());
+        assert!(!Path::new("a#").is_wildcard_all());
     }
 }
@@ -5,6 +5,7 @@
 // Bit flags passed to the "info" parameter of the iter function which
 // provides additional information about the data
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::mem;
 
@@ -20,10 +21,14 @@ struct InnerOptions<'a> {
     prefix: &'a str,
     // Indent is the nested indentation
     // Default is two spaces
-    indent: &'a str,
+    indent: Cow<'a, str>,
     // sort_keys will sort the keys alphabetically
     // Default is false
     sort_keys: bool,
+    // max_array_elements is the maximum number of elements written for each
+    // array. The remaining elements are replaced with a "... N more" string.
+    // Default is no limit
+    max_array_elements: usize,
 }
 
 pub struct PrettyOptions<'a> {
@@ -36,8 +41,9 @@ impl<'a> Default for PrettyOptions<'a> {
             inner: InnerOptions {
                 width: 80,
                 prefix: "",
-                indent: "  ",
+                indent: Cow::Borrowed("  "),
                 sort_keys: false,
+                max_array_elements: usize::MAX,
             },
         }
     }
@@ -56,19 +62,33 @@ impl<'a> PrettyOptions<'a> {
         self
     }
     pub fn indent(mut self, indent: &'a str) -> Self {
-        self.inner.indent = indent;
+        self.inner.indent = Cow::Borrowed(indent);
+        self
+    }
+    /// Sets the indent to a number of spaces.
+    pub fn indent_spaces(mut self, spaces: usize) -> Self {
+        self.inner.indent = Cow::Owned(" ".repeat(spaces));
         self
     }
     pub fn sort_keys(mut self, sort_keys: bool) -> Self {
         self.inner.sort_keys = sort_keys;
         self
     }
+    pub fn max_array_elements(mut self, max_array_elements: usize) -> Self {
+        self.inner.max_array_elements = max_array_elements;
+        self
+    }
     pub fn pretty<J>(&self, json: J) -> String
     where
         J: AsRef<str>,
     {
         pretty_options(json, self)
     }
+    /// Same as `pretty`, but for json that is a byte slice. Any invalid utf8
+    /// is replaced with the replacement character.
+    pub fn pretty_string(&self, json: &[u8]) -> String {
+        self.pretty(String::from_utf8_lossy(json))
+    }
 }
 
 pub fn pretty<J>(json: J) -> String
@@ -78,6 +98,12 @@ where
     PrettyOptions::default().pretty(json)
 }
 
+/// Same as `pretty`, but for json that is a byte slice. Any invalid utf8 is
+/// replaced with the replacement character.
+pub fn pretty_string(json: &[u8]) -> String {
+    PrettyOptions::default().pretty_string(json)
+}
+
 fn pretty_options<J>(json: J, opts: &PrettyOptions) -> String
 where
     J: AsRef<str>,
@@ -97,6 +123,7 @@ where
         prefix,
         opts.inner.indent.as_bytes(),
         opts.inner.sort_keys,
+        opts.inner.max_array_elements,
         0,
         0,
         -1,
@@ -117,6 +144,7 @@ fn extend_pretty_any(
     prefix: &[u8],
     indent: &[u8],
     sort_keys: bool,
+    max_elems: usize,
     tabs: i64,
     nl: i64,
     max: i64,
@@ -145,6 +173,7 @@ fn extend_pretty_any(
                 prefix,
                 indent,
                 sort_keys,
+                max_elems,
                 tabs,
                 nl,
                 max,
@@ -163,6 +192,7 @@ fn extend_pretty_any(
                 prefix,
                 indent,
                 sort_keys,
+                max_elems,
                 tabs,
                 nl,
                 max,
@@ -264,6 +294,7 @@ fn extend_pretty_object(
     prefix: &[u8],
     indent: &[u8],
     sort_keys: bool,
+    max_elems: usize,
     tabs: i64,
     mut nl: i64,
     max: i64,
@@ -292,6 +323,7 @@ fn extend_pretty_object(
                     prefix,
                     "".as_bytes(),
                     sort_keys,
+                    max_elems,
                     0,
                     0,
                     max,
@@ -357,6 +389,14 @@ fn extend_pretty_object(
                 }
                 extend_tabs(buf, prefix, indent, tabs + 1);
             }
+            if open == b'[' && n == max_elems {
+                // replace the remaining elements with a marker
+                let (count, next_i) = skip_elements(json, i);
+                buf.extend(format!("\"... {} more\"", count).as_bytes());
+                i = next_i;
+                n += 1;
+                continue;
+            }
             if open == b'{' {
                 let res = extend_pretty_string(buf, json, i, nl);
                 i = res.0;
@@ -378,6 +418,7 @@ fn extend_pretty_object(
                 prefix,
                 indent,
                 sort_keys,
+                max_elems,
                 tabs + 1,
                 nl,
                 max,
@@ -403,6 +444,47 @@ fn extend_pretty_object(
     (i, nl, open != b'{')
 }
 
+// skip_elements skips over the remaining elements of an array and returns
+// the number of elements skipped along with the index of the closing bracket.
+fn skip_elements(json: &[u8], mut i: usize) -> (usize, usize) {
+    let mut count = 0;
+    let mut depth = 0;
+    let mut in_elem = false;
+    while i < json.len() {
+        if depth == 0 && !in_elem && json[i] > b' ' && json[i] != b',' {
+            if json[i] == b']' || json[i] == b'}' {
+                break;
+            }
+            in_elem = true;
+            count += 1;
+        }
+        match json[i] {
+            b'"' => {
+                i += 1;
+                while i < json.len() {
+                    if json[i] == b'\\' {
+                        i += 1;
+                    } else if json[i] == b'"' {
+                        break;
+                    }
+                    i += 1;
+                }
+            }
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            b',' if depth == 0 => in_elem = false,
+            _ => {}
+        }
+        i += 1;
+    }
+    (count, i.min(json.len()))
+}
+
 fn sort_pairs(json: &[u8], buf: &mut Vec<u8>, pairs: &mut Vec<Pair>) {
     if pairs.len() == 0 {
         return;
@@ -442,12 +524,40 @@ fn extend_tabs(buf: &mut Vec<u8>, prefix: &[u8], indent: &[u8], tabs: i64) {
 }
 
 pub fn ugly(json: &str) -> String {
-    let src = json.as_bytes();
-    let mut dst = Vec::with_capacity(json.len());
+    let dst = ugly_bytes(json.as_bytes(), false);
+    unsafe { mem::transmute::<Vec<u8>, String>(dst) }
+}
+
+/// Same as `ugly`, but a single newline is kept between concatenated top-level
+/// values, such that `{"a": 1} {"a": 2}` becomes `{"a":1}` and `{"a":2}` on
+/// their own lines, like JSON Lines.
+pub fn ugly_preserve_lines(json: &str) -> Vec<u8> {
+    ugly_bytes(json.as_bytes(), true)
+}
+
+// ugly_bytes removes all whitespace outside of strings. When lines is true,
+// a newline is written between each top-level value.
+fn ugly_bytes(src: &[u8], lines: bool) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(src.len());
+    let mut depth = 0usize;
+    // A top-level value has ended, and the next one goes on a new line.
+    let mut ended = false;
     let mut i = 0;
     while i < src.len() {
         if src[i] > b' ' {
+            if lines && depth == 0 && ended {
+                dst.push(b'\n');
+                ended = false;
+            }
             dst.push(src[i]);
+            match src[i] {
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    ended = depth == 0;
+                }
+                _ => {}
+            }
             if src[i] == b'"' {
                 i += 1;
                 while i < src.len() {
@@ -466,11 +576,15 @@ pub fn ugly(json: &str) -> String {
                     }
                     i += 1;
                 }
+                ended = depth == 0;
             }
+        } else if depth == 0 && !dst.is_empty() {
+            // Whitespace ends a top-level number, true, false, or null.
+            ended = true;
         }
         i += 1;
     }
-    unsafe { mem::transmute::<Vec<u8>, String>(dst) }
+    dst
 }
 
 #[cfg(test)]
@@ -510,6 +624,18 @@ mod test {
         assert_eq!(super::ugly(EXAMPLE_PRETTY), EXAMPLE_UGLY);
         assert_eq!(super::pretty(super::ugly(EXAMPLE_PRETTY)), EXAMPLE_PRETTY);
     }
+    #[test]
+    fn ugly_preserve_lines() {
+        let json = "{\"a\": 1, \"b\": [1, 2]}{\"a\": 2}\n\n  [ \"x y\" ] \"s\" 12 true\n{}";
+        assert_eq!(
+            super::ugly_preserve_lines(json),
+            b"{\"a\":1,\"b\":[1,2]}\n{\"a\":2}\n[\"x y\"]\n\"s\"\n12\ntrue\n{}"
+        );
+        assert_eq!(super::ugly_preserve_lines(EXAMPLE_PRETTY), EXAMPLE_UGLY.as_bytes());
+        assert_eq!(super::ugly_preserve_lines("  \n "), b"");
+        assert_eq!(super::ugly_preserve_lines("[\"]\\\"\"]\n[]\n"), b"[\"]\\\"\"]\n[]");
+    }
+
     #[test]
     fn pretty() {
         assert_eq!(super::pretty(EXAMPLE_UGLY), EXAMPLE_PRETTY);
@@ -655,4 +781,50 @@ mod test {
 
         println!("{}", super::pretty(JSON));
     }
+
+    #[test]
+    fn max_array_elements() {
+        let json = "[1,2,3,4,5,6,7,8,9,10]";
+        let opts = super::PrettyOptions::new().max_array_elements(3);
+        assert_eq!(opts.pretty(json), "[1, 2, 3, \"... 7 more\"]\n");
+        let opts = opts.width(0);
+        assert_eq!(
+            opts.pretty(json),
+            "[\n  1,\n  2,\n  3,\n  \"... 7 more\"\n]\n"
+        );
+        let json = r#"{"a":[{"b":[1,2]},"x,y",[3,4]],"c":[]}"#;
+        let opts = super::PrettyOptions::new().max_array_elements(1);
+        let res = opts.pretty(json);
+        assert!(crate::valid(&res));
+        assert_eq!(super::ugly(&res), r#"{"a":[{"b":[1,"... 1 more"]},"... 2 more"],"c":[]}"#);
+        let res = super::PrettyOptions::new().max_array_elements(0).pretty("[1,2]");
+        assert_eq!(res, "[\"... 2 more\"]\n");
+        let res = super::PrettyOptions::new().max_array_elements(2).pretty("[1,2]");
+        assert_eq!(res, "[1, 2]\n");
+    }
+
+    #[test]
+    fn pretty_string() {
+        let json = r#"{"name":{"first":"Tom","last":"Anderson"},"age":37}"#;
+        assert_eq!(super::pretty_string(json.as_bytes()), super::pretty(json));
+        let opts = super::PrettyOptions::new().indent("\t").sort_keys(true);
+        assert_eq!(opts.pretty_string(json.as_bytes()), opts.pretty(json));
+        assert_eq!(super::pretty_string(b"[\"a\xffb\"]"), "[\"a\u{fffd}b\"]\n");
+    }
+
+    #[test]
+    fn indent_spaces() {
+        let json = r#"{"name":{"first":"Tom","last":"Anderson"},"nets":[{"a":1}]}"#;
+        for n in 0..8 {
+            let spaces = " ".repeat(n);
+            assert_eq!(
+                super::PrettyOptions::new().indent_spaces(n).pretty(json),
+                super::PrettyOptions::new().indent(&spaces).pretty(json)
+            );
+        }
+        assert_eq!(
+            super::PrettyOptions::new().indent_spaces(4).pretty(r#"{"a":1}"#),
+            "{\n    \"a\": 1\n}\n"
+        );
+    }
 }
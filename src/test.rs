@@ -89,6 +89,15 @@ fn modifiers() {
         get("[1,[2],[3,4],[5,[6,7]]]", r#"@flatten:{"deep":true}"#).json(),
         "[1,2,3,4,5,6,7]"
     );
+
+    assert_eq!(get(r#"[[1,2],[3],"x",[4]]"#, "@concat").json(), "[1,2,3,4]");
+    assert_eq!(
+        get(r#"[[1,2],[3],"x",[4]]"#, r#"@concat:{"keep":true}"#).json(),
+        r#"[1,2,3,"x",4]"#
+    );
+    assert_eq!(get("[[1,[2]],[ ],{},[3]]", "@concat").json(), "[1,[2],3]");
+    assert_eq!(get(r#"{"a":[1]}"#, "@concat").json(), r#"{"a":[1]}"#);
+    assert_eq!(get(EXAMPLE, "[children,friends.0.nets]|@concat.#").i32(), 6);
 }
 
 #[test]
@@ -232,7 +241,7 @@ fn escaped() {
 }
 
 #[cfg(test)]
-const EXAMPLE: &str = r#"
+pub(crate) const EXAMPLE: &str = r#"
 {
   "name": {"first": "Tom", "last": "Anderson"},
   "age":37,
@@ -275,6 +284,32 @@ fn fuzz() {
     }
 }
 
+#[test]
+fn fuzz_multibyte_literals() {
+    // Malformed true, false, and null literals that are followed by
+    // multibyte characters.
+    let inputs = [
+        "[tx\u{20AC}]",
+        "[nu\u{20AC}]",
+        "[fals\u{20AC}]",
+        "{\"a\":fx\u{20AC}\u{20AC}}",
+        "[t\u{1F600}]",
+        "tx\u{20AC}",
+    ];
+    let paths = ["0", "a", "@this", "@pretty", "@ugly", "@reverse", "..0", "0|@reverse"];
+    for json in &inputs {
+        for path in &paths {
+            exec_simple_fuzz(format!("{}{}", json, path).as_bytes());
+            let res = get(json, path);
+            assert!(std::str::from_utf8(res.json().as_bytes()).is_ok());
+            let res = parse(json);
+            assert!(std::str::from_utf8(res.get(path).json().as_bytes()).is_ok());
+        }
+    }
+    assert!(!get("[tx\u{20AC}]", "0").exists());
+    assert!(get("[true]", "0").exists());
+}
+
 #[test]
 fn array_value() {
     const PROGRAMMERS: &str = r#"
@@ -345,3 +380,1639 @@ fn bool_convert_query() {
     assert_eq!(get(JSON, r#"vals.#(b==~true)#.a"#).json(), "[1,2,6,7,8]");
     // assert_eq!(get(JSON, r#"vals.#(b==~false)#.a"#).json(), "[3,4,5,9,10,11]");
 }
+
+#[test]
+fn bare_modifiers() {
+    assert_eq!(get(EXAMPLE, "friends|length").i32(), 3);
+    assert_eq!(get(EXAMPLE, "friends|@length").i32(), 3);
+    assert_eq!(get(EXAMPLE, "friends.@length").i32(), 3);
+    assert_eq!(get(EXAMPLE, "name.first|length").i32(), 3);
+    assert_eq!(get(EXAMPLE, "name|keys").json(), r#"["first","last"]"#);
+    assert_eq!(get(EXAMPLE, "name|values").json(), r#"["Tom","Anderson"]"#);
+    assert_eq!(get(EXAMPLE, "name|keys|length").i32(), 2);
+    assert_eq!(get(EXAMPLE, "friends.#.first|length").i32(), 3);
+    assert_eq!(get(EXAMPLE, "name|keys.1").str(), "last");
+    // bare names are regular keys when not following a pipe
+    assert!(!get(EXAMPLE, "friends.length").exists());
+    assert_eq!(get(r#"{"length":5}"#, "length").i32(), 5);
+    // a member with the same key wins over the bare modifier
+    let json = r#"{"a":{"length":5,"keys":"k"},"b":{"x":1}}"#;
+    assert_eq!(get(json, "a|length").i32(), 5);
+    assert_eq!(get(json, "a|keys").str(), "k");
+    assert_eq!(get(json, "a|values").json(), r#"[5,"k"]"#);
+    assert_eq!(get(json, "a|@length").i32(), 2);
+    assert_eq!(get(json, "b|length").i32(), 1);
+}
+
+#[test]
+fn detach() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<OwnedValue>();
+
+    let json = EXAMPLE.to_owned();
+    let owned = get(&json, "friends.1").detach();
+    let escaped = get(r#"{"a":"Hello\nWorld"}"#, "a").detach();
+    drop(json);
+    let handle = std::thread::spawn(move || {
+        let value = owned.borrow();
+        assert!(value.kind() == Kind::Object);
+        assert_eq!(escaped.borrow().str(), "Hello\nWorld");
+        assert_eq!(
+            escaped.borrow().str().as_ptr(),
+            escaped.borrow().str().as_ptr()
+        );
+        value.get("first").str().to_owned()
+    });
+    assert_eq!(handle.join().unwrap(), "Roger");
+    assert!(!Value::default().detach().borrow().exists());
+}
+
+#[test]
+fn numeric_coercion_query() {
+    const JSON: &str = r#"
+    {
+        "items": [
+            {"name": "a", "weight": "44kg", "price": "5.00 USD"},
+            {"name": "b", "weight": "38kg", "price": "12.50 USD"},
+            {"name": "c", "weight": 41, "price": "free"},
+            {"name": "d", "weight": "heavy", "price": "$0.99"}
+        ]
+    }
+    "#;
+    assert_eq!(get(JSON, r#"items.#(weight>#40)#.name"#).json(), r#"["a","c"]"#);
+    assert_eq!(get(JSON, r#"items.#(weight<=#38)#.name"#).json(), r#"["b"]"#);
+    assert_eq!(get(JSON, r#"items.#(weight==#44)#.name"#).json(), r#"["a"]"#);
+    assert_eq!(get(JSON, r#"items.#(weight=#44)#.name"#).json(), r#"["a"]"#);
+    assert_eq!(get(JSON, r#"items.#(weight!=#44)#.name"#).json(), r#"["b","c"]"#);
+    assert_eq!(get(JSON, r#"items.#(price==#5)#.name"#).json(), r#"["a"]"#);
+    // only a sign may come before the number, so "$0.99" is not a number
+    assert_eq!(get(JSON, r#"items.#(price<#10)#.name"#).json(), r#"["a"]"#);
+    // without the '#' the strings are compared as strings
+    assert_eq!(get(JSON, r#"items.#(weight>40)#.name"#).json(), r#"["a","c","d"]"#);
+    // '~' still converts to bool
+    assert_eq!(get(JSON, r#"items.#(weight==~true)#.name"#).json(), r#"["c"]"#);
+    assert_eq!(get(JSON, r#"items.#(weight>~40)#.name"#).json(), "[]");
+}
+
+#[test]
+fn jsonlines_modifiers() {
+    let json = "[1,2]\n[3]\n\n[4,[5]]\n";
+    assert_eq!(get(json, "..#.@this|@flatten").json(), "[1,2,3,4,[5]]");
+    assert_eq!(get(json, "..@flatten").json(), "[1,2,3,4,[5]]");
+    assert_eq!(
+        get(json, r#"..@flatten:{"deep":true}"#).json(),
+        "[1,2,3,4,5]"
+    );
+    assert_eq!(get(json, "..@this").json(), "[[1,2],[3],[4,[5]]]");
+    assert_eq!(get(json, "..@reverse|0").json(), "[4,[5]]");
+    assert_eq!(get(json, "..@flatten.#").i32(), 5);
+}
+
+#[test]
+fn indexed() {
+    let root = parse(EXAMPLE);
+    let (value, index) = root.get_indexed("friends.1.last");
+    let index = index.unwrap();
+    assert_eq!(&EXAMPLE[index..index + value.json().len()], r#""Craig""#);
+    assert_eq!(value.index(), Some(index));
+
+    let (value, index) = root.get_indexed("name");
+    let index = index.unwrap();
+    assert_eq!(
+        &EXAMPLE[index..index + value.json().len()],
+        r#"{"first": "Tom", "last": "Anderson"}"#
+    );
+
+    let spliced = format!(
+        "{}{}{}",
+        &EXAMPLE[..index],
+        r#""Tom Anderson""#,
+        &EXAMPLE[index + value.json().len()..]
+    );
+    assert_eq!(get(&spliced, "name").str(), "Tom Anderson");
+
+    let (value, index) = root.get_indexed("friends.#.first");
+    assert!(value.exists());
+    assert_eq!(index, None);
+    assert_eq!(root.get_indexed("missing").1, None);
+}
+
+#[test]
+fn large_counts() {
+    let n = 250_000;
+    let mut json = String::with_capacity(n * 2 + 2);
+    json.push('[');
+    for i in 0..n {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push('0');
+    }
+    json.push(']');
+    assert_eq!(get(&json, "#").json(), "250000");
+    assert_eq!(get(&json, "@length").json(), "250000");
+    assert_eq!(get(&json, "#(==0)#|#").json(), "250000");
+}
+
+#[test]
+fn try_get_path() {
+    fn friend_age(json: &str, i: usize) -> Result<i64, PathMiss> {
+        let friends = try_get(json, "friends")?;
+        let path = format!("{}.age", i);
+        Ok(friends.try_get(&path)?.i64())
+    }
+    assert_eq!(friend_age(EXAMPLE, 1), Ok(68));
+    let err = friend_age(EXAMPLE, 5).unwrap_err();
+    assert_eq!(err.path(), "5.age");
+    assert_eq!(err.to_string(), "path not found: 5.age");
+    assert_eq!(try_get(EXAMPLE, "name.middle").err().unwrap().path(), "name.middle");
+    assert_eq!(try_get(EXAMPLE, "name.first").ok().unwrap().str(), "Tom");
+}
+
+#[test]
+fn wildcard_children() {
+    const JSON: &str = r#"
+    {
+        "users": {
+            "u1": {"first": "Dale", "age": 44},
+            "u2": {"first": "Roger", "age": 68},
+            "u3": {"age": 47},
+            "u4": {"first": "Jane", "age": 52}
+        },
+        "tags": {"a": "x", "b": "y"}
+    }
+    "#;
+    assert_eq!(get(JSON, "users.*#.first").json(), r#"["Dale","Roger","Jane"]"#);
+    assert_eq!(get(JSON, "users.*#.age").json(), "[44,68,47,52]");
+    assert_eq!(get(JSON, "users.*#.first|1").str(), "Roger");
+    assert_eq!(get(JSON, "users.*#.first|#").i32(), 3);
+    assert_eq!(get(JSON, "tags.*#").json(), r#"["x","y"]"#);
+    assert_eq!(get(JSON, "tags.*#|#").i32(), 2);
+    assert_eq!(get(JSON, "*#.a").json(), r#"["x"]"#);
+    assert_eq!(get(EXAMPLE, "friends.*#.first").json(), r#"["Dale","Roger","Jane"]"#);
+    assert_eq!(get(EXAMPLE, "children.*#").json(), r#"["Sara","Alex","Jack"]"#);
+    assert_eq!(get(EXAMPLE, "friends.*#.nets.*#").json(), r#"[["ig","fb","tw"],["fb","tw"],["ig","tw"]]"#);
+    assert_eq!(get(EXAMPLE, "friends.*#.missing").json(), "[]");
+    assert!(!get(EXAMPLE, "age.*#").exists());
+    // wildcards within a key still match the first key
+    assert_eq!(get(EXAMPLE, "child*.2").str(), "Jack");
+    assert_eq!(get(r#"{"*":1,"a":2}"#, r#"\*"#).i32(), 1);
+    // a standalone `*` is a key pattern that matches the first key
+    assert_eq!(get(JSON, "users.*.first").str(), "Dale");
+    assert_eq!(get(JSON, "users.*.age").i32(), 44);
+    assert_eq!(get(JSON, "tags.*").str(), "x");
+    assert_eq!(get(EXAMPLE, "name.*").str(), "Tom");
+    assert!(!get(EXAMPLE, "children.*").exists());
+    // the example from the README
+    let json = r#"{"friends": {"u1": {"first": "Dale"}, "u2": {"first": "Roger"}}}"#;
+    assert_eq!(get(json, "friends.*.first").str(), "Dale");
+    assert_eq!(get(json, "friends.*#.first").json(), r#"["Dale","Roger"]"#);
+}
+
+#[test]
+fn each_indexed() {
+    let mut all = Vec::new();
+    get(EXAMPLE, "children").each_indexed(|i, value| {
+        all.push((i, value.str().to_owned()));
+        true
+    });
+    assert_eq!(
+        all,
+        [(0, "Sara".to_owned()), (1, "Alex".to_owned()), (2, "Jack".to_owned())]
+    );
+
+    let mut all = Vec::new();
+    get(EXAMPLE, "friends.0").each_indexed(|i, value| {
+        all.push((i, value.json().to_owned()));
+        i < 1
+    });
+    assert_eq!(all, [(0, r#""Dale""#.to_owned()), (1, r#""Murphy""#.to_owned())]);
+}
+
+#[test]
+fn compact() {
+    assert_eq!(
+        get(r#"{"a":1,"b":null,"c":"x"}"#, "@compact").json(),
+        r#"{"a":1,"c":"x"}"#
+    );
+    assert_eq!(get("[1, null, 2, null]", "@compact").json(), "[1,2]");
+    let json = r#"{"a":1,"b":null,"c":[1,null,{"d":null,"e":2}],"f":{"g":null}}"#;
+    assert_eq!(
+        get(json, "@compact").json(),
+        r#"{"a":1,"c":[1,null,{"d":null,"e":2}],"f":{"g":null}}"#
+    );
+    assert_eq!(
+        get(json, r#"@compact:{"deep":true}"#).json(),
+        r#"{"a":1,"c":[1,{"e":2}],"f":{}}"#
+    );
+    assert_eq!(
+        get(json, r#"@compact:{"deep":true,"removeEmpty":true}"#).json(),
+        r#"{"a":1,"c":[1,{"e":2}]}"#
+    );
+    assert_eq!(
+        get(r#"{"a":null,"b":[]}"#, r#"@compact:{"removeEmpty":true}"#).json(),
+        "{}"
+    );
+    assert!(valid(get(json, r#"@compact:{"deep":true}"#).json()));
+    assert_eq!(get(r#""x""#, "@compact").json(), r#""x""#);
+}
+
+#[test]
+fn structurally_eq() {
+    let a = parse(r#"{"a":1,"b":{"c":[1,2,{"x":"y","z":null}]}}"#);
+    let b = parse(r#"{ "b": {"c": [1, 2.0, {"z":null, "x":"y"}]}, "a": 1 }"#);
+    assert!(a.structurally_eq(&b));
+    assert!(b.structurally_eq(&a));
+    assert!(a != b);
+
+    let c = parse(r#"{"a":1,"b":{"c":[2,1,{"x":"y","z":null}]}}"#);
+    assert!(!a.structurally_eq(&c));
+    let d = parse(r#"{"a":1,"b":{"c":[1,2,{"x":"Y","z":null}]}}"#);
+    assert!(!a.structurally_eq(&d));
+    let e = parse(r#"{"a":1}"#);
+    assert!(!a.structurally_eq(&e));
+    assert!(!e.structurally_eq(&a));
+    assert!(parse(r#"{"a":1,"b":2}"#).structurally_eq(&parse(r#"{"b":2,"a":1}"#)));
+    assert!(!parse(r#"{"a":1,"b":2}"#).structurally_eq(&parse(r#"{"b":2,"c":1}"#)));
+    assert!(parse(r#""ab""#).structurally_eq(&parse(r#""ab""#)));
+    assert!(!parse("[1]").structurally_eq(&parse(r#"{"0":1}"#)));
+}
+
+#[test]
+fn spread() {
+    let json = r#"[{"a":1,"b":2},{"c":3},"x",{"a":4}]"#;
+    assert_eq!(get(json, "@spread").json(), r#"{"b":2,"c":3,"a":4}"#);
+    // @join keeps the first position of the overlapping key
+    assert_eq!(get(json, "@join").json(), r#"{"a":4,"b":2,"c":3}"#);
+    assert_eq!(get(json, "@spread.a").i32(), 4);
+    assert_eq!(get("[]", "@spread").json(), "{}");
+    assert_eq!(get(r#"{"a":1}"#, "@spread").json(), r#"{"a":1}"#);
+    assert!(valid(get(json, "@spread").json()));
+}
+
+#[test]
+fn object_query() {
+    const JSON: &str = r#"
+    {
+        "attributes": {"x": 1, "y": -2, "z": 3},
+        "people": {
+            "tom": {"age": 37, "city": "Phoenix"},
+            "jane": {"age": 47, "city": "Tempe"},
+            "dale": {"age": 44, "city": "Phoenix"}
+        }
+    }
+    "#;
+    assert_eq!(get(JSON, "attributes.#(>0)").i32(), 1);
+    assert_eq!(get(JSON, "attributes.#(>0)#").json(), "[1,3]");
+    assert_eq!(get(JSON, "attributes.#(<0)#").json(), "[-2]");
+    assert_eq!(get(JSON, "attributes.#(>10)#").json(), "[]");
+    assert!(!get(JSON, "attributes.#(>10)").exists());
+    assert_eq!(get(JSON, r#"people.#(city=="Phoenix").age"#).i32(), 37);
+    assert_eq!(get(JSON, r#"people.#(city=="Phoenix")#.age"#).json(), "[37,44]");
+    assert_eq!(get(JSON, r#"people.#(age>40)#|#"#).i32(), 2);
+}
+
+#[test]
+fn with_key_raw() {
+    let json = r#"{"name": {"first" : "Tom", "la\"st":"Anderson"}, "age":37}"#;
+    assert_eq!(get(json, "name.first").with_key_raw(), Some(r#""first" : "Tom""#));
+    assert_eq!(get(json, r#"name.la"st"#).with_key_raw(), Some(r#""la\"st":"Anderson""#));
+    assert_eq!(
+        get(json, "name").with_key_raw(),
+        Some(r#""name": {"first" : "Tom", "la\"st":"Anderson"}"#)
+    );
+    assert_eq!(get(json, "age").with_key_raw(), Some(r#""age":37"#));
+    assert_eq!(parse(json).get("name").get("first").with_key_raw(), Some(r#""first" : "Tom""#));
+    assert_eq!(get(json, "name|first").with_key_raw(), Some(r#""first" : "Tom""#));
+
+    // conversions keep the member
+    let value = get(json, "name.first");
+    assert_eq!(value.detach().borrow().with_key_raw(), Some(r#""first" : "Tom""#));
+    assert_eq!(get_urlpath(json, "name.first").with_key_raw(), Some(r#""first" : "Tom""#));
+    assert_eq!(get(json, "@this.name.first").with_key_raw(), Some(r#""first" : "Tom""#));
+    assert_eq!(get(json, "@ugly|name.first").with_key_raw(), Some(r#""first":"Tom""#));
+
+    // values that are not object members
+    assert_eq!(get(EXAMPLE, "children.1").with_key_raw(), None);
+    assert_eq!(get(EXAMPLE, "friends.#.first").with_key_raw(), None);
+    assert_eq!(get(json, "name.@this").with_key_raw(), None);
+    assert_eq!(parse(json).with_key_raw(), None);
+
+    // locate the member in the original json
+    let value = get(json, "age");
+    let member = value.with_key_raw().unwrap();
+    let start = value.index().unwrap() + value.json().len() - member.len();
+    assert_eq!(&json[start..start + member.len()], member);
+}
+
+#[test]
+fn depth_limit() {
+    // the nets arrays are nested four levels deep
+    assert_eq!(get_with_depth(EXAMPLE, "friends.1.nets.0", 4).str(), "fb");
+    assert!(!get_with_depth(EXAMPLE, "friends.1.nets.0", 3).exists());
+    assert!(!get_with_depth(EXAMPLE, "friends|1|nets|0", 3).exists());
+    // only the objects and arrays that are entered count
+    assert_eq!(get_with_depth(EXAMPLE, "friends.1.first", 3).str(), "Roger");
+    assert_eq!(get_with_depth(EXAMPLE, "friends", 1).get("1.nets.0").str(), "fb");
+    assert_eq!(
+        get_with_depth(EXAMPLE, r#"friends.#(nets.#(=="fb"))#.first"#, 4).json(),
+        r#"["Dale","Roger"]"#
+    );
+    assert!(!get_with_depth(EXAMPLE, r#"friends.#(nets.#(=="fb"))#.first"#, 1).exists());
+    assert_eq!(get_with_depth(EXAMPLE, r#"friends.#(nets.#(=="fb"))#.first"#, 3).json(), "[]");
+    assert_eq!(get_with_depth(EXAMPLE, "friends.#.nets.0", 3).json(), "[]");
+    assert_eq!(get_with_depth(EXAMPLE, "[age,friends.1.nets.0]", 3).json(), "[37]");
+    assert_eq!(get_with_depth(EXAMPLE, "friends.1.@reverse", 3).json(), "");
+    assert_eq!(get_with_depth(EXAMPLE, "friends.1|@reverse", 4).get("first").str(), "Roger");
+    // brackets in strings are ignored
+    assert_eq!(get_with_depth(r#"{"a":"[[[[{{{{\"[[["}"#, "a", 1).str(), "[[[[{{{{\"[[[");
+
+    // deeply nested input must not overflow the stack
+    let n = 1_000_000;
+    let deep = format!("{}{}", "[".repeat(n), "]".repeat(n));
+    assert!(!get_with_depth(&deep, "@flatten:{\"deep\":true}", 500).exists());
+    assert_eq!(get_with_depth(&deep, "#.@flatten:{\"deep\":true}", 500).json(), "[]");
+    assert!(!get_with_depth(&deep, "0|@pretty", 500).exists());
+    assert!(!get_with_depth(&deep, "0.0.0.0", 3).exists());
+    assert!(get_with_depth(&deep, "0.0", 3).exists());
+    let path = format!("{}{}", "#(".repeat(n), ")".repeat(n));
+    assert!(!get_with_depth(EXAMPLE, &path, 500).exists());
+}
+
+#[test]
+fn first_existing() {
+    let json = r#"{"a":{"x":1},"legacy":"old"}"#;
+    let value = parse(json);
+    assert_eq!(value.first_existing(&["a.b", "a.c", "legacy"]).str(), "old");
+    assert_eq!(value.first_existing(&["a.x", "legacy"]).i32(), 1);
+    assert!(!value.first_existing(&["a.b", "a.c"]).exists());
+    assert!(!value.first_existing(&[]).exists());
+}
+
+#[test]
+fn all_exist_any_exist() {
+    let value = parse(EXAMPLE);
+    let required = ["name.first", "age", "friends.#(last=\"Murphy\").first", "fav\\.movie"];
+    assert!(value.all_exist(&required));
+    assert!(value.any_exist(&required));
+    let partial = ["name.first", "name.middle", "age"];
+    assert!(!value.all_exist(&partial));
+    assert!(value.any_exist(&partial));
+    assert!(!value.any_exist(&["name.middle", "weight"]));
+    // no paths are required, but none of them exist either
+    assert!(value.all_exist(&[]));
+    assert!(!value.any_exist(&[]));
+}
+
+#[test]
+fn is_empty() {
+    let json = r#"{"arr":[],"obj":{ },"ws":[
+    ],"str":"","one":[1],"nested":[[]],"s":" ","esc":"\u0000","null":null,"zero":0,"f":false}"#;
+    let value = parse(json);
+    for path in ["arr", "obj", "ws", "str", "missing", "arr.0"] {
+        assert!(value.get(path).is_empty(), "{}", path);
+    }
+    for path in ["one", "nested", "s", "esc", "null", "zero", "f", "@this"] {
+        assert!(!value.get(path).is_empty(), "{}", path);
+    }
+    assert!(parse("  []  ").is_empty());
+    assert!(parse("").is_empty());
+    assert!(get(EXAMPLE, "friends.#(age>100)#").is_empty());
+    assert!(!get(EXAMPLE, "friends.#(age>40)#").is_empty());
+}
+
+#[test]
+fn as_object_as_array() {
+    let json = r#"{"obj":{"a":1},"arr":[1,2],"num":1,"str":"[1]","nul":null}"#;
+    let value = parse(json);
+    assert_eq!(value.get("obj").as_object().unwrap().get("a").i32(), 1);
+    assert!(value.get("obj").as_array().is_none());
+    assert_eq!(value.get("arr").as_array().unwrap().array().len(), 2);
+    assert!(value.get("arr").as_object().is_none());
+    for path in ["num", "str", "nul", "missing"] {
+        assert!(value.get(path).as_object().is_none());
+        assert!(value.get(path).as_array().is_none());
+    }
+    let arr = value.get("num");
+    assert_eq!(arr.as_array().map(|a| a.array().len()).unwrap_or(0), 0);
+}
+
+#[test]
+fn to_lines() {
+    let json = r#"{"name":{"first":"Tom","last":"Anderson"},"age":37,"nets":["fb","tw"]}"#;
+    let value = get(json, "[name.first,age,nets]");
+    assert_eq!(value.to_lines(), "\"Tom\"\n37\n[\"fb\",\"tw\"]");
+    let value = get(json, "{name.first,age}");
+    assert_eq!(value.to_lines(), "first\t\"Tom\"\nage\t37");
+    assert_eq!(get(json, "age").to_lines(), "37");
+    assert_eq!(get(json, "[]").to_lines(), "");
+    assert_eq!(get(json, "missing").to_lines(), "");
+}
+
+#[test]
+fn number_str() {
+    let json = r#"{"a":10.10,"b":-0.5e+10,"c":"10.10","d":true,"e":null,"f":[1]}"#;
+    assert_eq!(get(json, "a").number_str(), Some("10.10"));
+    assert_eq!(get(json, "b").number_str(), Some("-0.5e+10"));
+    assert_eq!(get("12345678901234567890.123", "@this").number_str(), Some("12345678901234567890.123"));
+    for path in ["c", "d", "e", "f", "missing"] {
+        assert_eq!(get(json, path).number_str(), None);
+    }
+}
+
+#[test]
+fn each_matching() {
+    let json = r#"{"user_1":"Tom","admin":"Jane","user_2":"Dale","user":"x","user_3":"Roger"}"#;
+    let value = parse(json);
+    let mut names = Vec::new();
+    value.each_matching("user_*", |key, value| {
+        names.push(format!("{}={}", key.str(), value.str()));
+        true
+    });
+    assert_eq!(names, ["user_1=Tom", "user_2=Dale", "user_3=Roger"]);
+    let mut count = 0;
+    value.each_matching("user_*", |_, _| {
+        count += 1;
+        false
+    });
+    assert_eq!(count, 1);
+    let mut count = 0;
+    parse(r#"["user_1","user_2"]"#).each_matching("*", |_, _| {
+        count += 1;
+        true
+    });
+    parse(r#""user_1""#).each_matching("*", |_, _| {
+        count += 1;
+        true
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn root_modifier() {
+    assert_eq!(get(EXAMPLE, "friends.0|@root|age").i32(), 37);
+    assert_eq!(get(EXAMPLE, "friends.#.first|@root.name.last").str(), "Anderson");
+    assert_eq!(get(EXAMPLE, "name|[first,@root.age]").json(), r#"["Tom",37]"#);
+    assert_eq!(get(EXAMPLE, "friends|{count:#,age:@root.age}").json(), r#"{"count":3,"age":37}"#);
+    assert_eq!(get(EXAMPLE, "@root.children.1").str(), "Alex");
+    let path = crate::path::Path::new("@root");
+    let json = r#"{"a":1}"#;
+    let (res, _) = crate::modifiers::exec(b"[1,2]", json.as_bytes(), path);
+    assert_eq!(res.json(), json);
+}
+
+#[test]
+fn get_sep() {
+    use crate::get_sep;
+    assert_eq!(get_sep(EXAMPLE, "fav.movie", '/').str(), "Deer Hunter");
+    assert_eq!(get_sep(EXAMPLE, "name/last", '/').str(), "Anderson");
+    assert_eq!(get_sep(EXAMPLE, "children/1", '/').str(), "Alex");
+    assert_eq!(get_sep(EXAMPLE, "children/#", '/').i32(), 3);
+    assert_eq!(get_sep(EXAMPLE, "friends/#/first", '/').json(), r#"["Dale","Roger","Jane"]"#);
+    assert_eq!(get_sep(EXAMPLE, "friends/#(last==\"Murphy\")#/first", '/').json(), r#"["Dale","Jane"]"#);
+    assert_eq!(get_sep(EXAMPLE, "friends/1|last", '/').str(), "Craig");
+    assert_eq!(get_sep(EXAMPLE, "[name.first,age]/0", '/').str(), "Tom");
+    assert!(!get_sep(EXAMPLE, "name.last", '/').exists());
+    let json = r#"{"a.b":{"c/d":{"e":1}}}"#;
+    assert_eq!(get_sep(json, "a.b/c\\/d/e", '/').i32(), 1);
+    assert_eq!(get_sep(json, "a.b:c/d:e", ':').i32(), 1);
+    let root = parse(EXAMPLE);
+    let (value, index) = root.get_indexed("fav\\.movie");
+    assert!(get_sep(EXAMPLE, "fav.movie", '/') == value);
+    assert_eq!(get_sep(EXAMPLE, "fav.movie", '/').index(), index);
+}
+
+#[test]
+fn as_str() {
+    let json = r#"{"a":"hello","b":"\u0048i\n","c":"","d":10,"e":true,"f":{"x":1},"g":null,"h":["a"]}"#;
+    let value = parse(json);
+    assert_eq!(value.get("a").as_str(), Some("hello"));
+    assert_eq!(value.get("b").as_str(), Some("Hi\n"));
+    assert_eq!(value.get("c").as_str(), Some(""));
+    for path in ["d", "e", "f", "g", "h", "missing"] {
+        assert_eq!(value.get(path).as_str(), None);
+    }
+}
+
+#[test]
+fn loose_equality() {
+    let json = r#"[
+        {"id":"123","name":"a"},
+        {"id":123,"name":"b"},
+        {"id":123.0,"name":"c"},
+        {"id":"123abc","name":"d"},
+        {"id":true,"name":"e"},
+        {"id":"true","name":"f"},
+        {"id":null,"name":"g"}
+    ]"#;
+    // strings are compared as text and numbers by value
+    assert_eq!(get(json, "#(id==123.0)#.name").json(), r#"["b","c"]"#);
+    assert_eq!(get(json, "#(id~==123.0)#.name").json(), r#"["a","b","c"]"#);
+    assert_eq!(get(json, "#(id~==123)#.name").json(), r#"["a","b","c"]"#);
+    assert_eq!(get(json, r#"#(id~=="123")#.name"#).json(), r#"["a","b","c"]"#);
+    assert_eq!(get(json, "#(id ~== 1.23e2)#.name").json(), r#"["a","b","c"]"#);
+    assert_eq!(get(json, "#(id~==123abc).name").str(), "d");
+    assert_eq!(get(json, "#(id~==true)#.name").json(), r#"["e","f"]"#);
+    assert_eq!(get(json, "#(id~==null).name").str(), "g");
+    assert_eq!(get(json, "#(id~==124)#.name").json(), "[]");
+    assert_eq!(get(r#"{"a~b":1}"#, "a~b").i32(), 1);
+}
+
+#[test]
+fn for_each_raw() {
+    let json = r#"[1, "a\"b", {"x": [1, 2]}, [true, false, null], -2.5e3, "\u00e9"]"#;
+    let value = parse(json);
+    let mut raw = Vec::new();
+    value.for_each_raw(|elem| {
+        raw.push(std::str::from_utf8(elem).unwrap().to_owned());
+        true
+    });
+    let mut expect = Vec::new();
+    value.each(|_, elem| {
+        expect.push(elem.json().to_owned());
+        true
+    });
+    assert_eq!(raw, expect);
+    assert_eq!(format!("[{}]", raw.join(", ")), json);
+
+    let mut raw = Vec::new();
+    parse(r#"{"a":1,"b":"x","c":{"d":null}}"#).for_each_raw(|elem| {
+        raw.push(elem.to_vec());
+        raw.len() < 2
+    });
+    assert_eq!(raw, [b"1".to_vec(), br#""x""#.to_vec()]);
+
+    let mut raw = Vec::new();
+    parse("[1,tru").for_each_raw(|elem| {
+        raw.push(elem.to_vec());
+        true
+    });
+    assert_eq!(raw, [b"1".to_vec()]);
+    let mut count = 0;
+    parse("12").for_each_raw(|elem| {
+        assert_eq!(elem, b"12");
+        count += 1;
+        true
+    });
+    Value::default().for_each_raw(|_| {
+        count += 1;
+        true
+    });
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn compiled_path() {
+    use crate::CompiledPath;
+    let paths = [
+        "name.last",
+        "friends.1.first",
+        "friends.2.nets.0",
+        "fav\\.movie",
+        "child*.2",
+        "c?ildren.0",
+        "friends.#.first",
+        "friends.#(last==\"Murphy\")#.first",
+        "name|@reverse",
+        "[age,name.first]",
+        "friends.*.age",
+        "missing.path",
+        "friends.9",
+        "",
+    ];
+    let docs = [
+        EXAMPLE.to_owned(),
+        EXAMPLE.replace("Murphy", "Smith"),
+        r#"{"name":"Tom","friends":[1,2]}"#.to_owned(),
+        "[1,2,3]".to_owned(),
+        "".to_owned(),
+    ];
+    for path in paths {
+        let compiled = CompiledPath::new(path);
+        assert_eq!(compiled.path(), path);
+        for _ in 0..100 {
+            for json in &docs {
+                let expect = get(json, path);
+                let res = compiled.get(json);
+                assert!(res == expect, "path {} in {}", path, json);
+                assert_eq!(res.exists(), expect.exists());
+                assert_eq!(res.index(), expect.index());
+            }
+        }
+    }
+    assert!(CompiledPath::new("name.last").comps.is_some());
+    assert!(CompiledPath::new("friends.#.first").comps.is_none());
+}
+
+#[test]
+fn base64() {
+    let json = r#"{"name":"hello","payload":"eyJpZCI6MSwidGFncyI6WyJhIl19","bad":"*not base64*","num":12}"#;
+    assert_eq!(get(json, "name.@base64e").str(), "aGVsbG8=");
+    assert_eq!(get(json, "name.@base64e.@base64d").str(), "hello");
+    assert_eq!(get(json, "payload.@base64d").str(), r#"{"id":1,"tags":["a"]}"#);
+    assert_eq!(get(get(json, "payload.@base64d").str(), "tags.0").str(), "a");
+    assert!(!get(json, "bad.@base64d").exists());
+    assert!(!get(json, "num.@base64d").exists());
+    assert!(!get(json, "missing.@base64e").exists());
+    assert!(!get(r#""/w==""#, "@base64d").exists());
+    assert_eq!(get(json, "num.@base64e").str(), "MTI=");
+    assert_eq!(get(r#"{"a":1}"#, "@base64e").str(), "eyJhIjoxfQ==");
+    assert_eq!(get(r#"{"a":1}"#, "@base64e.@base64d").str(), r#"{"a":1}"#);
+    assert_eq!(get(r#""line\nbreak \"q\"""#, "@base64e.@base64d").str(), "line\nbreak \"q\"");
+}
+
+#[test]
+fn query_by_length() {
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#>2).first").str(), "Dale");
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#>2)#.first").json(), r#"["Dale"]"#);
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#==2)#.first").json(), r#"["Roger","Jane"]"#);
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#<3)#.last").json(), r#"["Craig","Murphy"]"#);
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#>=2)#|#").i32(), 3);
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#(==\"ig\"))#.first").json(), r#"["Dale","Jane"]"#);
+    assert_eq!(get(EXAMPLE, "friends.#(nets.#>2).nets.#").i32(), 3);
+    let json = "[[1,2],[1,2,3],[],[4,5,6,7]]";
+    assert_eq!(get(json, "#(#>2)#").json(), "[[1,2,3],[4,5,6,7]]");
+    assert_eq!(get(json, "#(#==0)").json(), "[]");
+    assert_eq!(get(json, "#(#)#|#").i32(), 4);
+    let json = r#"{"a":{"b":[1,2,3]},"c":{"b":[1]}}"#;
+    assert_eq!(get(json, "#(b.#>1)").json(), r#"{"b":[1,2,3]}"#);
+}
+
+#[test]
+fn modifier_then_path() {
+    assert_eq!(get(EXAMPLE, "name.@keys.1").str(), "last");
+    assert_eq!(get(EXAMPLE, "name.@keys.0").str(), "first");
+    assert_eq!(get(EXAMPLE, "name.@keys.#").i32(), 2);
+    assert!(!get(EXAMPLE, "name.@keys.2").exists());
+    assert_eq!(get(EXAMPLE, "name.@values.1").str(), "Anderson");
+    assert_eq!(get(EXAMPLE, "name.@keys|1").str(), "last");
+    assert_eq!(get(EXAMPLE, "children.@reverse.0").str(), "Jack");
+    assert_eq!(get(EXAMPLE, "friends.0.@keys.3").str(), "nets");
+}
+
+#[test]
+fn get_bytes_lossy() {
+    use crate::get_bytes_lossy;
+    let json = b"{\"name\":\"Tom\xff\xfeA\",\"bad\xc3\":1,\"age\":37,\"nets\":[\"\xe2\x82\"]}";
+    assert_eq!(get_bytes_lossy(json, "age").i32(), 37);
+    assert_eq!(get_bytes_lossy(json, "name").str(), "Tom\u{fffd}\u{fffd}A");
+    assert_eq!(get_bytes_lossy(json, "bad\u{fffd}").i32(), 1);
+    assert_eq!(get_bytes_lossy(json, "nets.0").str(), "\u{fffd}");
+    // offsets into the replaced json are not offsets into the input
+    assert_eq!(get_bytes_lossy(json, "age").index(), None);
+    assert_eq!(get_bytes_lossy(EXAMPLE.as_bytes(), "age").index(), get(EXAMPLE, "age").index());
+    assert!(!get_bytes_lossy(json, "missing").exists());
+    let value = {
+        let bytes = EXAMPLE.as_bytes().to_vec();
+        get_bytes_lossy(&bytes, "name.last")
+    };
+    assert_eq!(value.str(), "Anderson");
+    for i in 0..=255u8 {
+        let json = [b'[', i, b',', b'1', b']'];
+        let _ = get_bytes_lossy(&json, "1");
+        let _ = get_bytes_lossy(&json, "0");
+    }
+}
+
+#[test]
+fn try_each() {
+    let value = get(EXAMPLE, "friends");
+    let mut count = 0;
+    assert!(value.try_each(|_, _| {
+        count += 1;
+        true
+    }));
+    assert_eq!(count, 3);
+    // stop at the first match
+    let mut found = None;
+    let completed = value.try_each(|_, friend| {
+        if friend.get("age").i32() > 45 {
+            found = Some(friend.get("first").str().to_owned());
+            return false;
+        }
+        true
+    });
+    assert!(!completed);
+    assert_eq!(found.as_deref(), Some("Roger"));
+    // stopping on the last child still reports an early stop
+    let mut count = 0;
+    assert!(!value.try_each(|_, _| {
+        count += 1;
+        count < 3
+    }));
+    assert!(get(EXAMPLE, "name").try_each(|_, _| true));
+    assert!(get("[]", "@this").try_each(|_, _| false));
+    assert!(get(EXAMPLE, "missing").try_each(|_, _| false));
+}
+
+#[test]
+fn pick() {
+    let json = r#"{"id":1,"name":"Tom","secret":"x","address":{"city":"Tempe","zip":"85281"}}"#;
+    assert_eq!(get(json, r#"@pick:{"fields":["id","name"]}"#).json(), r#"{"id":1,"name":"Tom"}"#);
+    assert_eq!(get(json, r#"@pick:{"fields":["name","id"]}"#).json(), r#"{"name":"Tom","id":1}"#);
+    assert_eq!(
+        get(json, r#"@pick:{"fields":["id","address.city","missing"]}"#).json(),
+        r#"{"id":1,"address.city":"Tempe"}"#
+    );
+    assert_eq!(get(json, r#"address.@pick:{"fields":["zip"]}"#).json(), r#"{"zip":"85281"}"#);
+    assert_eq!(get(json, r#"@pick:{"fields":["id","name"]}.name"#).str(), "Tom");
+    assert_eq!(get(json, "@pick").json(), "{}");
+    assert!(!get(json, r#"name.@pick:{"fields":["id"]}"#).exists());
+    assert_eq!(
+        get(EXAMPLE, r#"friends.#.@pick:{"fields":["first","age"]}"#).json(),
+        r#"[{"first":"Dale","age":44},{"first":"Roger","age":68},{"first":"Jane","age":47}]"#
+    );
+}
+
+#[test]
+fn omit() {
+    let json = r#"{"secret":"x","name":"Tom","token":"y"}"#;
+    let res = get(json, r#"@omit:{"fields":["secret","token"]}"#);
+    assert_eq!(res.json(), r#"{"name":"Tom"}"#);
+    assert!(crate::valid(res.json()));
+    assert_eq!(get(json, r#"@omit:{"fields":["name"]}"#).json(), r#"{"secret":"x","token":"y"}"#);
+    assert_eq!(get(json, r#"@omit:{"fields":["missing"]}"#).json(), json);
+    assert_eq!(get(json, "@omit").json(), json);
+    assert_eq!(get(json, r#"@omit:{"fields":["secret","name","token"]}"#).json(), "{}");
+    let json = r#"{"a":1,"b":{"c":2,"d":{"e":3,"f":4}},"g.h":5,"i":[1]}"#;
+    assert_eq!(
+        get(json, r#"@omit:{"fields":["b.c","b.d.e","g\\.h","i.0"]}"#).json(),
+        r#"{"a":1,"b":{"d":{"f":4}},"i":[1]}"#
+    );
+    assert_eq!(get(json, r#"@omit:{"fields":["b.c","b"]}"#).json(), r#"{"a":1,"g.h":5,"i":[1]}"#);
+    assert_eq!(get(json, r#"@omit:{"fields":["a.b"]}"#).json(), json);
+    // json that is not an object passes through
+    assert_eq!(get(r#"[1,2]"#, r#"@omit:{"fields":["0"]}"#).json(), "[1,2]");
+    assert_eq!(get(r#""a""#, r#"@omit:{"fields":["a"]}"#).json(), r#""a""#);
+    assert!(!get(r#"[1,2]"#, r#"@pick:{"fields":["0"]}"#).exists());
+    assert!(!get(r#""a""#, r#"@pick:{"fields":["a"]}"#).exists());
+    assert_eq!(
+        get(EXAMPLE, r#"friends.#.@omit:{"fields":["nets","age","last"]}"#).json(),
+        r#"[{"first":"Dale"},{"first":"Roger"},{"first":"Jane"}]"#
+    );
+}
+
+#[test]
+fn filterkeys() {
+    let json = r#"{"user_id":1,"name":"Tom","user_tags":["a","b"],"\u0075ser_x":{"y":2},"users":3}"#;
+    let res = get(json, r#"@filterkeys:"user_*""#);
+    assert_eq!(res.json(), r#"{"user_id":1,"user_tags":["a","b"],"\u0075ser_x":{"y":2}}"#);
+    assert!(crate::valid(res.json()));
+    assert_eq!(res.get("user_x.y").i32(), 2);
+    assert_eq!(get(json, "@filterkeys:user_*|@keys").json(), r#"["user_id","user_tags","\u0075ser_x"]"#);
+    assert_eq!(get(json, r#"@filterkeys:"*s""#).json(), r#"{"user_tags":["a","b"],"users":3}"#);
+    assert_eq!(get(json, r#"@filterkeys:"none*""#).json(), "{}");
+    assert_eq!(get(json, "@filterkeys").json(), "{}");
+    assert!(!get(json, r#"user_tags.@filterkeys:"*""#).exists());
+    assert_eq!(
+        get(EXAMPLE, r#"friends.#.@filterkeys:"*a*""#).json(),
+        r#"[{"last":"Murphy","age":44},{"last":"Craig","age":68},{"last":"Murphy","age":47}]"#
+    );
+}
+
+#[test]
+fn round() {
+    assert_eq!(get("3.14159", "@round:2").json(), "3.14");
+    assert_eq!(get("3.14159", "@round:4").json(), "3.1416");
+    assert_eq!(get("3.14159", "@round").json(), "3");
+    assert_eq!(get("42", "@round:0").json(), "42");
+    assert_eq!(get("42", "@round:3").json(), "42");
+    let json = r#"{"pi":3.14159,"n":[2.5,-2.5,10,-0.004,1.005e2],"s":"1.2345","big":12345678901234567890}"#;
+    let res = get(json, "@round:2");
+    assert_eq!(res.json(), r#"{"pi":3.14,"n":[2.5,-2.5,10,0,100.5],"s":"1.2345","big":12345678901234567890}"#);
+    assert!(crate::valid(res.json()));
+    assert_eq!(
+        get(json, "@round").json(),
+        r#"{"pi":3,"n":[3,-3,10,0,101],"s":"1.2345","big":12345678901234567890}"#
+    );
+    assert_eq!(get("[1.5e300,0.1]", "@round:1").json(), "[1.5e300,0.1]");
+    assert_eq!(get("[1.25]", "@round:20").json(), "[1.25]");
+    assert_eq!(get("[1.25]", "@round:-1").json(), "[1.25]");
+    assert_eq!(get(json, "n.@round:1|0").f64(), 2.5);
+    assert!(!get(json, "missing.@round").exists());
+}
+
+#[test]
+fn limit() {
+    assert_eq!(get("[1,2,3,4]", "@limit:2").json(), "[1,2]");
+    assert_eq!(get("[1,2,3,4]", "@limit:10").json(), "[1,2,3,4]");
+    assert_eq!(get("[1,2,3,4]", "@limit:0").json(), "[]");
+    assert_eq!(get("[1,2,3,4]", "@limit:-1").json(), "[]");
+    assert_eq!(get("[1, 2, 3]", "@limit").json(), "[1, 2, 3]");
+    assert_eq!(get("[]", "@limit:1").json(), "[]");
+    assert_eq!(get(r#"{"a":1}"#, "@limit:1").json(), r#"{"a":1}"#);
+    assert_eq!(
+        get(EXAMPLE, "friends.#(age>40)#|@limit:2").json(),
+        r#"[{"first": "Dale", "last": "Murphy", "age": 44, "nets": ["ig", "fb", "tw"]},{"first": "Roger", "last": "Craig", "age": 68, "nets": ["fb", "tw"]}]"#
+    );
+    assert_eq!(get(EXAMPLE, "friends.#(age>40)#.first|@limit:1").json(), r#"["Dale"]"#);
+    assert_eq!(get(EXAMPLE, "friends.#(age>40)#|@limit:2|#").i32(), 2);
+    assert_eq!(get(EXAMPLE, "friends.#(age>40)#|@limit:2|1.first").str(), "Roger");
+}
+
+#[test]
+fn hash() {
+    use std::collections::HashSet;
+    let json = r#"[1, 1.0, 10e-1, -0, 0, "a", "\u0061", true, true, null, {"a":1}, {"a":1}, {"a": 1}, [1], "1"]"#;
+    let mut set = HashSet::new();
+    let root = parse(json);
+    for value in root.array() {
+        set.insert(value);
+    }
+    // 1, 0, "a", true, null, {"a":1}, {"a": 1}, [1], "1"
+    assert_eq!(set.len(), 9);
+    assert!(set.contains(&get(json, "1")));
+    assert!(set.contains(&get(r#"{"x":"a"}"#, "x")));
+    assert!(set.contains(&get(r#"{"x":{"a":1}}"#, "x")));
+    assert!(!set.contains(&get(r#"{"x":2}"#, "x")));
+    let reversed = get(json, "@reverse");
+    let owned: Vec<_> = reversed.array().iter().map(|v| v.detach()).collect();
+    for value in &owned {
+        assert!(set.contains(&value.borrow()));
+    }
+}
+
+#[test]
+fn as_bool() {
+    let json = r#"{"t":true,"f":false,"st":"true","n":1,"z":0,"o":{"a":true},"a":[true],"nul":null}"#;
+    let value = parse(json);
+    assert_eq!(value.get("t").as_bool(), Some(true));
+    assert_eq!(value.get("f").as_bool(), Some(false));
+    for path in ["st", "n", "z", "o", "a", "nul", "missing"] {
+        assert_eq!(value.get(path).as_bool(), None);
+    }
+    assert!(value.get("st").bool());
+    assert!(value.get("n").bool());
+}
+
+#[test]
+fn logical_queries() {
+    assert_eq!(get(EXAMPLE, r#"friends.#(age>40 && last=="Murphy")#.first"#).json(), r#"["Dale","Jane"]"#);
+    assert_eq!(get(EXAMPLE, r#"friends.#(age>45 && last=="Murphy").first"#).str(), "Jane");
+    assert_eq!(get(EXAMPLE, r#"friends.#(age>60 || first=="Dale")#.first"#).json(), r#"["Dale","Roger"]"#);
+    assert_eq!(get(EXAMPLE, r#"friends.#(age<45||age>60)#.first"#).json(), r#"["Dale","Roger"]"#);
+    // && binds tighter than ||
+    assert_eq!(
+        get(EXAMPLE, r#"friends.#(first=="Roger" || age>40 && last=="Murphy" && age<45)#.first"#).json(),
+        r#"["Dale","Roger"]"#
+    );
+    assert_eq!(get(EXAMPLE, r#"friends.#(nets.#(=="fb") && age>50)#.first"#).json(), r#"["Roger"]"#);
+    assert_eq!(get(EXAMPLE, r#"friends.#(age>100 && first=="Dale")#"#).json(), "[]");
+    let json = r#"[{"a":"x && y"},{"a":"x"},{"a":"y || z"}]"#;
+    assert_eq!(get(json, r#"#(a=="x && y")#|#"#).i32(), 1);
+    assert_eq!(get(json, r#"#(a=="x" || a=="y || z")#.a"#).json(), r#"["x","y || z"]"#);
+}
+
+#[test]
+fn get_or() {
+    let value = parse(EXAMPLE);
+    assert_eq!(value.get_or("count", "0").i64(), 0);
+    assert_eq!(value.get_or("age", "0").i64(), 37);
+    assert_eq!(value.get_or("name.middle", r#""n/a""#).str(), "n/a");
+    assert_eq!(value.get_or("missing", r#"{"a":[1,2]}"#).get("a.1").i32(), 2);
+    assert_eq!(value.get_or("children", "[]").array().len(), 3);
+    assert!(!value.get_or("missing", "").exists());
+}
+
+#[test]
+fn get_path_bytes() {
+    use crate::get_path_bytes;
+    assert_eq!(get_path_bytes(EXAMPLE, b"name.last").str(), "Anderson");
+    assert_eq!(get_path_bytes(EXAMPLE, b"friends.#.first").json(), r#"["Dale","Roger","Jane"]"#);
+    let buf = b"xxchildren.1xx".to_vec();
+    assert_eq!(get_path_bytes(EXAMPLE, &buf[2..12]).str(), "Alex");
+    assert!(!get_path_bytes(EXAMPLE, b"name.\xff").exists());
+    assert!(!get_path_bytes(r#"{"\u00e9":1}"#, b"\xc3").exists());
+    assert_eq!(get_path_bytes(r#"{"é":1}"#, "é".as_bytes()).i32(), 1);
+}
+
+#[test]
+fn raw_trimmed() {
+    let value = parse("  {\"a\":1}  ");
+    assert_eq!(value.json(), "{\"a\":1}  ");
+    assert_eq!(value.raw_trimmed(), "{\"a\":1}");
+    assert_eq!(parse("\n\t[1, 2]\r\n").raw_trimmed(), "[1, 2]");
+    assert_eq!(parse("  12  ").raw_trimmed(), "12");
+    assert_eq!(parse(" \" a \" ").raw_trimmed(), "\" a \"");
+    assert_eq!(get(EXAMPLE, "name").raw_trimmed(), get(EXAMPLE, "name").json());
+    assert_eq!(parse("   ").raw_trimmed(), "");
+}
+
+#[test]
+fn query_count() {
+    assert_eq!(get(EXAMPLE, "friends.#(age>40)#.#").i32(), 3);
+    assert_eq!(get(EXAMPLE, "friends.#(age>45)#.#").i32(), 2);
+    assert_eq!(get(EXAMPLE, "friends.#(age>100)#.#").i32(), 0);
+    assert_eq!(get(EXAMPLE, r#"friends.#(last=="Murphy")#.#"#).i32(), 2);
+    assert_eq!(get(EXAMPLE, "friends.#(age>45)#|#").i32(), 2);
+    assert_eq!(get(EXAMPLE, "friends.#(age>45)#.first.#").json(), "[]");
+    assert_eq!(parse(EXAMPLE).get("friends").get("#(age>45)#.#").i32(), 2);
+}
+
+#[test]
+fn owned_copies() {
+    let value = get(EXAMPLE, "name.first");
+    assert_eq!(value.owned_json(), value.json());
+    assert_eq!(value.owned_str(), value.str());
+    let (json, s) = {
+        let src = String::from(r#"{"a":"hi\nthere","b":[1,2]}"#);
+        let a = get(&src, "a");
+        (get(&src, "b").owned_json(), a.owned_str())
+    };
+    assert_eq!(json, "[1,2]");
+    assert_eq!(s, "hi\nthere");
+    let (json, s) = {
+        let value = get(EXAMPLE, "friends.#.first");
+        (value.owned_json(), value.get("1").owned_str())
+    };
+    assert_eq!(json, r#"["Dale","Roger","Jane"]"#);
+    assert_eq!(s, "Roger");
+}
+
+#[test]
+fn get_any_pointer() {
+    assert_eq!(get_any(EXAMPLE, "name.last").str(), "Anderson");
+    assert_eq!(get_any(EXAMPLE, "/name/last").str(), "Anderson");
+    assert_eq!(get_any(EXAMPLE, "friends.1.nets.0").json(), get_any(EXAMPLE, "/friends/1/nets/0").json());
+    assert_eq!(get_any(EXAMPLE, "/fav.movie").str(), "Deer Hunter");
+    assert!(!get_any(EXAMPLE, "/children/3").exists());
+    assert!(!get_any(EXAMPLE, "/nothing").exists());
+    let json = r#"{"/a":1,"a":{"b~c":2,"d/e":3,"f g":4}}"#;
+    assert_eq!(get_any(json, "/a").i32(), 1);
+    assert_eq!(get_any(json, "/a/b~0c").i32(), 2);
+    assert_eq!(get_any(json, "/a/d~1e").i32(), 3);
+    assert_eq!(get_any(json, "/a/f g").i32(), 4);
+    assert_eq!(get_any(json, "/a/b~0c").index, Some(19));
+    // the pointer `/` is the empty key
+    let json = r#"{"":1,"a":2,"b":{"":{"c":[3,4]}}}"#;
+    assert_eq!(get_any(json, "/").i32(), 1);
+    assert_eq!(get_any(json, "/").index, Some(4));
+    assert_eq!(get_any(json, "/b//c/1").i32(), 4);
+    assert_eq!(get_any(json, "/b//c/1").index, Some(28));
+    assert!(!get_any(json, "/b//c/01").exists());
+    assert!(!get_any(json, "/b//c/2").exists());
+    assert!(!get_any(json, "/a/0").exists());
+    assert_eq!(get_any(r#"[{"x":1}]"#, "/0/x").i32(), 1);
+    assert_eq!(get_any(r#"{"a\u002fb":5}"#, "/a~1b").i32(), 5);
+}
+
+#[test]
+fn get_urlpath_encoded() {
+    let json = r#"{"first name":"Tom","a.b":{"c d":[1,{"e%f":2}]},"a":{"b":3},"*":4,"user_id":5}"#;
+    assert_eq!(get_urlpath(json, "first%20name").str(), "Tom");
+    assert_eq!(get_urlpath(json, "first name").str(), "Tom");
+    assert_eq!(get_urlpath(json, "a%2Eb.c%20d.1.e%25f").i32(), 2);
+    assert_eq!(get_urlpath(json, "a.b").i32(), 3);
+    assert_eq!(get_urlpath(json, "%2A").i32(), 4);
+    assert_eq!(get_urlpath(json, "user%5Fid").i32(), 5);
+    assert!(!get_urlpath(json, "user_*").exists());
+    assert!(!get_urlpath(json, "a%2Eb.c%20d.#").exists());
+    assert!(!get_urlpath(json, "first%2520name").exists());
+    let path = String::from("first%20name");
+    let value = get_urlpath(json, &path);
+    drop(path);
+    assert_eq!(value.index(), Some(14));
+    assert_eq!(value.str(), "Tom");
+    assert_eq!(get_urlpath(EXAMPLE, "fav%2Emovie").str(), "Deer Hunter");
+}
+
+#[test]
+fn sibling_count() {
+    let value = get_with_meta(EXAMPLE, "children.1");
+    assert_eq!(value.str(), "Alex");
+    assert_eq!(value.sibling_count(), Some(3));
+    assert_eq!(value.ordinal(), Some(1));
+    assert_eq!(value.detach().borrow().sibling_count(), Some(3));
+    assert_eq!(get(EXAMPLE, "children.1").sibling_count(), None);
+    assert_eq!(get_with_meta(EXAMPLE, "friends|2.first").sibling_count(), None);
+    assert_eq!(get_with_meta(EXAMPLE, "friends|2").sibling_count(), Some(3));
+    assert_eq!(get_with_meta(EXAMPLE, "friends.0.nets.1").sibling_count(), Some(3));
+    assert_eq!(get_with_meta(EXAMPLE, "friends.1.nets.1").sibling_count(), Some(2));
+    let value = get_with_meta(EXAMPLE, r#"friends.#(last=="Murphy")"#);
+    assert_eq!(value.get("first").str(), "Dale");
+    assert_eq!(value.index(), get(EXAMPLE, r#"friends.#(last=="Murphy")"#).index());
+    assert_eq!(value.sibling_count(), Some(3));
+    assert_eq!(get_with_meta(EXAMPLE, "friends.@reverse.0").sibling_count(), Some(3));
+    assert_eq!(get_with_meta("[1,[2,3]]", "1").sibling_count(), Some(2));
+    assert_eq!(get_with_meta("[1,[2,3]]", "1.0").sibling_count(), Some(2));
+    assert_eq!(get_with_meta("{\"a\":1}\n{\"a\":2}\n[]", "..1").sibling_count(), Some(3));
+    assert_eq!(get_with_meta(EXAMPLE, "age").sibling_count(), None);
+    assert_eq!(get_with_meta(EXAMPLE, "children.5").sibling_count(), None);
+    assert_eq!(get_with_meta(EXAMPLE, "children").sibling_count(), None);
+    assert_eq!(get_with_meta(EXAMPLE, "friends.#.nets.1").sibling_count(), None);
+    assert_eq!(
+        get_with_meta(EXAMPLE, "friends.#.nets.1").json(),
+        get(EXAMPLE, "friends.#.nets.1").json()
+    );
+}
+
+#[test]
+fn walk() {
+    let mut count = 0;
+    let mut leaves = 0;
+    parse(EXAMPLE).walk(|path, value| {
+        count += 1;
+        if value.is_scalar() {
+            leaves += 1;
+            let path = path.join(".").replace("fav.movie", "fav\\.movie");
+            assert_eq!(get(EXAMPLE, &path).json(), value.json());
+        }
+    });
+    assert_eq!(count, 33);
+    assert_eq!(leaves, 23);
+    let mut paths = Vec::new();
+    get(EXAMPLE, "friends.1").walk(|path, _| paths.push(path.join("/")));
+    assert_eq!(paths, ["", "first", "last", "age", "nets", "nets/0", "nets/1"]);
+    let mut count = 0;
+    get(EXAMPLE, "missing").walk(|_, _| count += 1);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn i64_exact() {
+    assert_eq!(parse("1e3").i64(), 1000);
+    assert_eq!(parse("1e3").i64_exact(), Some(1000));
+    assert_eq!(parse("2.5e2").i64(), 250);
+    assert_eq!(parse("2.5e2").i64_exact(), Some(250));
+    assert_eq!(parse("2.5").i64(), 2);
+    assert_eq!(parse("2.5").i64_exact(), None);
+    assert_eq!(parse("-2.5e-1").i64_exact(), None);
+    assert_eq!(parse("-10.0").i64_exact(), Some(-10));
+    assert_eq!(parse("9223372036854775807").i64_exact(), Some(i64::MAX));
+    assert_eq!(parse("9223372036854775808").i64_exact(), None);
+    assert_eq!(parse("1e19").i64_exact(), None);
+    assert_eq!(parse(r#""12""#).i64_exact(), None);
+    assert_eq!(get(EXAMPLE, "age").i64_exact(), Some(37));
+    assert_eq!(get(EXAMPLE, "friends.#").i64_exact(), Some(3));
+}
+
+#[test]
+fn wildcard_keys_all() {
+    let json = r#"{"children":["Sara","Alex","Jack"],"childData":[1,2,3],"child#":4,"other":[5,6,7]}"#;
+    // the first key, in document order, where the rest of the path exists
+    assert_eq!(get(json, "child*.2").json(), r#""Jack""#);
+    assert_eq!(get(json, "child?a*.1").json(), "2");
+    assert_eq!(get(json, "child*#.2").json(), r#"["Jack",3]"#);
+    assert_eq!(get(json, "child*#").json(), r#"[["Sara","Alex","Jack"],[1,2,3],4]"#);
+    assert_eq!(get(json, "child*#.#").json(), "[3,3]");
+    assert_eq!(get(json, "nothing*#").json(), "[]");
+    assert_eq!(get(json, r"child*\#").json(), "4");
+    assert_eq!(get(json, "child#").json(), "4");
+    assert_eq!(get(EXAMPLE, "name.*t#").json(), r#"["Tom","Anderson"]"#);
+}
+
+#[test]
+fn typed_arrays() {
+    assert_eq!(parse("[1,2,3]").f64_array(false), [1.0, 2.0, 3.0]);
+    assert_eq!(parse("[1,2,3]").f64_array(true), [1.0, 2.0, 3.0]);
+    assert_eq!(parse(r#"["a","b"]"#).str_array(), ["a", "b"]);
+    let mixed = parse(r#"[1.5,"x",null,"2",true,-3]"#);
+    assert_eq!(mixed.f64_array(true), [1.5, -3.0]);
+    assert_eq!(mixed.f64_array(false), [1.5, 0.0, 0.0, 2.0, 1.0, -3.0]);
+    assert_eq!(mixed.str_array(), ["1.5", "x", "", "2", "true", "-3"]);
+    assert_eq!(get(EXAMPLE, "friends.#.age").f64_array(true), [44.0, 68.0, 47.0]);
+    assert_eq!(get(EXAMPLE, "children").str_array(), ["Sara", "Alex", "Jack"]);
+    assert!(get(EXAMPLE, "age").f64_array(false).is_empty());
+    assert!(get(EXAMPLE, "name").str_array().is_empty());
+}
+
+#[test]
+fn container_or_scalar() {
+    for json in &["{}", r#"{"a":1}"#, "[]", "[1,2]"] {
+        assert!(parse(json).is_container());
+        assert!(!parse(json).is_scalar());
+    }
+    for json in &["1", r#""a""#, "true", "false", "null", ""] {
+        assert!(!parse(json).is_container());
+        assert!(parse(json).is_scalar());
+    }
+    assert!(get(EXAMPLE, "friends.0").is_container());
+    assert!(get(EXAMPLE, "friends.0.age").is_scalar());
+}
+
+#[test]
+fn query_ordinal() {
+    assert_eq!(get(EXAMPLE, r#"friends.#(last=="Murphy")"#).ordinal(), Some(0));
+    assert_eq!(get(EXAMPLE, "friends.#(age>45)").ordinal(), Some(1));
+    assert_eq!(get(EXAMPLE, r#"friends.#(first=="Jane")"#).ordinal(), Some(2));
+    assert_eq!(get(EXAMPLE, r#"friends.#(first=="Jane").first"#).ordinal(), None);
+    assert_eq!(get(EXAMPLE, r#"friends.#(first=="Nobody")"#).ordinal(), None);
+    assert_eq!(get(EXAMPLE, "friends.2").ordinal(), Some(2));
+    assert_eq!(get(EXAMPLE, "friends").ordinal(), None);
+    let friends = get(EXAMPLE, "friends");
+    assert_eq!(friends.get("#(age>45)").ordinal(), Some(1));
+    assert_eq!(friends.get("#(age>45)").detach().borrow().ordinal(), Some(1));
+    let json = r#"{"attributes":{"x":-1,"y":2,"z":3}}"#;
+    assert_eq!(get(json, "attributes.#(>0)").ordinal(), Some(1));
+}
+
+#[test]
+fn key_interner() {
+    let mut interner = KeyInterner::new();
+    assert!(interner.is_empty());
+    let docs: Vec<String> = (0..100)
+        .map(|i| format!(r#"{{"id":{},"name":"user{}","tags":[],"id":{}}}"#, i, i, i + 1))
+        .collect();
+    let mut first_key = None;
+    for (i, doc) in docs.iter().enumerate() {
+        let value = parse(doc);
+        let map = value.map_with_interner(&mut interner);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["id"].i64(), i as i64 + 1);
+        assert_eq!(map["name"].str(), format!("user{}", i));
+        let (key, _) = map.get_key_value("name").unwrap();
+        match &first_key {
+            None => first_key = Some(key.clone()),
+            Some(first) => assert!(Arc::ptr_eq(first, key)),
+        }
+    }
+    assert_eq!(interner.len(), 3);
+    let value = parse("[1,2]");
+    assert!(value.map_with_interner(&mut interner).is_empty());
+    let name = get(EXAMPLE, "name");
+    let map = name.map_with_interner(&mut interner);
+    assert_eq!(map["last"].str(), "Anderson");
+    assert_eq!(interner.len(), 5);
+    interner.clear();
+    assert!(interner.is_empty());
+}
+
+#[test]
+fn empty_path() {
+    assert_eq!(get(EXAMPLE, "").json(), parse(EXAMPLE).json());
+    assert_eq!(get(EXAMPLE, "").get("age").i32(), 37);
+    assert_eq!(get(EXAMPLE, "").json(), get(EXAMPLE, "@this").json());
+    assert_eq!(get(" 12 ", "").i32(), 12);
+    assert_eq!(get(r#""a\nb""#, "").str(), "a\nb");
+    assert!(!get("", "").exists());
+    let value = get(EXAMPLE, "friends.1");
+    assert_eq!(value.get("").json(), value.json());
+    assert_eq!(value.get("").index(), value.index());
+    assert_eq!(value.get("").ordinal(), Some(1));
+    let first = get(EXAMPLE, "name.first");
+    assert_eq!(first.get("").with_key_raw(), Some(r#""first": "Tom""#));
+    let owned = get(EXAMPLE, "friends.#.first");
+    assert_eq!(owned.get("").json(), r#"["Dale","Roger","Jane"]"#);
+    assert_eq!(CompiledPath::new("").get(EXAMPLE).json(), parse(EXAMPLE).json());
+}
+
+#[test]
+fn explode() {
+    let json = r#"{"tags":"a,b,c","words":"one, two, three","esc":"x\"y|z\n","empty":"","n":1}"#;
+    assert_eq!(get(json, r#"tags.@explode:",""#).json(), r#"["a","b","c"]"#);
+    assert_eq!(get(json, r#"tags.@explode:{"sep":","}"#).json(), r#"["a","b","c"]"#);
+    assert_eq!(get(json, r#"words.@explode:", ""#).json(), r#"["one","two","three"]"#);
+    assert_eq!(get(json, r#"words.@explode:{"sep":", t"}"#).json(), r#"["one","wo","hree"]"#);
+    assert_eq!(get(json, r#"esc.@explode:"|""#).json(), r#"["x\"y","z\n"]"#);
+    assert_eq!(get(json, r#"tags.@explode:";""#).json(), r#"["a,b,c"]"#);
+    assert_eq!(get(json, r#"tags.@explode:"""#).json(), r#"["a,b,c"]"#);
+    assert_eq!(get(json, r#"empty.@explode:",""#).json(), "[]");
+    assert_eq!(get(json, r#"n.@explode:",""#).json(), "1");
+    assert_eq!(get(json, r#"tags.@explode:",".1"#).str(), "b");
+    assert_eq!(get(json, r#"tags.@explode:","|#"#).i32(), 3);
+}
+
+#[test]
+fn implode() {
+    let json = r#"{"tags":["a","b","c"],"nums":[1,2.5,-3],"mixed":["x\"y",true,null,[1, 2],{"a":1}],"none":[]}"#;
+    assert_eq!(get(json, r#"tags.@implode:",""#).json(), r#""a,b,c""#);
+    assert_eq!(get(json, r#"tags.@implode:{"sep":" - "}"#).str(), "a - b - c");
+    assert_eq!(get(json, r#"nums.@implode:"+""#).str(), "1+2.5+-3");
+    assert_eq!(get(json, r#"mixed.@implode:"|""#).str(), r#"x"y|true||[1, 2]|{"a":1}"#);
+    assert_eq!(get(json, r#"none.@implode:",""#).json(), r#""""#);
+    assert_eq!(get(json, r#"tags.@implode:"""#).str(), "abc");
+    assert_eq!(get(json, r#"tags.0.@implode:",""#).json(), r#""a""#);
+    assert_eq!(
+        get(json, r#"tags.@implode:",".@explode:",""#).json(),
+        r#"["a","b","c"]"#
+    );
+    assert_eq!(get(EXAMPLE, r#"children.@implode:", ""#).str(), "Sara, Alex, Jack");
+}
+
+#[test]
+fn eq_raw_normalized() {
+    let a = parse(r#"{ "a": 1, "b": [ 1, 2 ], "c": "x y" }"#);
+    let b = parse("{\"a\":1,\n\t\"b\":[1,2],\"c\":\"x y\"}");
+    assert!(a.eq_raw_normalized(&b));
+    assert!(b.eq_raw_normalized(&a));
+    assert!(!a.eq_raw_normalized(&parse(r#"{"a":1,"b":[1,2],"c":"xy"}"#)));
+    assert!(!a.eq_raw_normalized(&parse(r#"{"a":2,"b":[1,2],"c":"x y"}"#)));
+    assert!(!a.eq_raw_normalized(&parse(r#"{"b":[1,2],"a":1,"c":"x y"}"#)));
+    assert!(parse(r#"["a \" b"]"#).eq_raw_normalized(&parse(r#"[ "a \" b" ]"#)));
+    assert!(get(EXAMPLE, "friends.1").eq_raw_normalized(&parse(
+        r#"{"first":"Roger","last":"Craig","age":68,"nets":["fb","tw"]}"#
+    )));
+    assert!(!get(EXAMPLE, "missing").eq_raw_normalized(&parse("1")));
+}
+
+#[test]
+fn to_vec() {
+    let value = get(EXAMPLE, "friends.1");
+    let bytes = value.to_vec();
+    assert_eq!(bytes, value.json().as_bytes());
+    assert!(parse(std::str::from_utf8(&bytes).unwrap()) == value);
+    let value = get(EXAMPLE, "friends.#.nets");
+    let bytes = value.to_vec();
+    assert_eq!(bytes, br#"[["ig", "fb", "tw"],["fb", "tw"],["ig", "tw"]]"#);
+    assert!(parse(std::str::from_utf8(&bytes).unwrap()) == value);
+    assert_eq!(get(EXAMPLE, "name.first").to_vec(), br#""Tom""#);
+    assert_eq!(parse(r#""a\nb""#).to_vec(), br#""a\nb""#);
+    assert!(get(EXAMPLE, "missing").to_vec().is_empty());
+}
+
+#[test]
+fn query_regex() {
+    let json = r#"{"users":[
+        {"name":"ann","email":"ann@example.com"},
+        {"name":"bob","email":"bob@example.org"},
+        {"name":"cat","email":"cat@sub.example.com"},
+        {"name":"dan","email":"dan@examplexcom"},
+        {"name":"eve","email":12}
+    ]}"#;
+    let path = r#"users.#(email~"@example\\.com$")#.name"#;
+    let inverse = r#"users.#(email!~"@example\\.com$")#.name"#;
+    if cfg!(feature = "regex") {
+        assert_eq!(get(json, path).json(), r#"["ann"]"#);
+        assert_eq!(get(json, inverse).json(), r#"["bob","cat","dan"]"#);
+        assert_eq!(get(json, r#"users.#(email~"^[a-c]")#.name"#).json(), r#"["ann","bob","cat"]"#);
+        assert_eq!(get(json, r#"users.#(email~"(?i)^BOB@").name"#).str(), "bob");
+        assert_eq!(get(json, r#"users.#(email~"~")#.name"#).json(), "[]");
+        assert_eq!(get(json, r#"users.#(email~"(")#.name"#).json(), "[]");
+        assert_eq!(get(json, r#"users.#(email!~"(")#.name"#).json(), "[]");
+        assert_eq!(get(EXAMPLE, r#"friends.#(first~"^[DR]")#.first"#).json(), r#"["Dale","Roger"]"#);
+        assert_eq!(get(EXAMPLE, r#"friends.#(first!~"^[DR]")#.first"#).json(), r#"["Jane"]"#);
+    }
+    // % remains a wildcard match
+    assert_eq!(get(json, r#"users.#(email%"*example.com")#.name"#).json(), r#"["ann","cat"]"#);
+    assert_eq!(get(EXAMPLE, "friends.#(age>#40)#.first").json(), r#"["Dale","Roger","Jane"]"#);
+}
+
+#[test]
+#[cfg(not(feature = "regex"))]
+fn query_regex_without_feature() {
+    // the operators are rejected, rather than never or always matching
+    assert!(!get(EXAMPLE, r#"friends.#(first~"^[DR]")#.first"#).exists());
+    assert!(!get(EXAMPLE, r#"friends.#(first!~"^[DR]")#.first"#).exists());
+    assert!(!get(EXAMPLE, r#"friends.#(first!~"^[DR]").first"#).exists());
+    assert!(!get(EXAMPLE, r#"friends.#(age>40 || first~"^D")#"#).exists());
+    assert!(!get(EXAMPLE, r#"friends.#(first~"^D")#.#"#).exists());
+    assert_eq!(get(EXAMPLE, r#"friends.#(first=="Dale")#.first"#).json(), r#"["Dale"]"#);
+}
+
+#[test]
+fn escaped_keys_large_object() {
+    let mut json = String::from("{");
+    for i in 0..10000 {
+        json.push_str(&format!(r#""k\u0065y{}":{},"#, i, i));
+    }
+    json.push_str(r#""\u0074arget":"first-escaped","t\u0061rget2":"later-escaped","t\\r":1}"#);
+    assert_eq!(get(&json, "target").str(), "first-escaped");
+    assert_eq!(get(&json, "target2").str(), "later-escaped");
+    assert_eq!(get(&json, "key9999").i32(), 9999);
+    assert!(!get(&json, "missing").exists());
+    assert_eq!(get(&json, "key12").i32(), 12);
+    assert_eq!(get(&json, "k?y12").i32(), 12);
+    assert_eq!(get(&json, "*y13").i32(), 13);
+    assert_eq!(get(&json, "t*2").str(), "later-escaped");
+    assert_eq!(get(&json, r"t\\r").i32(), 1);
+    assert!(!get(&json, "k").exists());
+    assert!(!get(&json, "x*").exists());
+}
+
+#[test]
+fn each_matching_streams() {
+    let mut json = String::from("{");
+    for i in 0..1000 {
+        json.push_str(&format!(r#""user_{}":{{"id":{}}},"other_{}":{},"#, i, i, i, i));
+    }
+    json.push_str(r#""user_last":{"id":-1}}"#);
+    let value = parse(&json);
+    let mut count = 0;
+    let mut sum = 0;
+    value.each_matching("user_*", |key, value| {
+        assert!(key.str().starts_with("user_"));
+        // the value borrows from the original json rather than a copy
+        assert!(value.owned.is_empty());
+        assert_eq!(&json[value.index().unwrap()..][..value.json().len()], value.json());
+        sum += value.get("id").i64();
+        count += 1;
+        true
+    });
+    assert_eq!(count, 1001);
+    assert_eq!(sum, 999 * 1000 / 2 - 1);
+    let mut seen = Vec::new();
+    value.each_matching("user_1?", |key, _| {
+        seen.push(key.str().to_owned());
+        seen.len() < 3
+    });
+    assert_eq!(seen, ["user_10", "user_11", "user_12"]);
+    assert_eq!(get(&json, "user_*#").get("#").i32(), 1001);
+}
+
+#[test]
+fn get_index() {
+    let friends = get(EXAMPLE, "friends");
+    for i in 0..4 {
+        let a = friends.get_index(i);
+        let path = i.to_string();
+        let b = friends.get(&path);
+        assert!(a == b);
+        assert_eq!(a.exists(), b.exists());
+        assert_eq!(a.index(), b.index());
+        assert_eq!(a.ordinal(), b.ordinal());
+    }
+    assert_eq!(friends.get_index(2).get("first").str(), "Jane");
+    let firsts = get(EXAMPLE, "friends.#.first");
+    assert_eq!(firsts.get_index(2).str(), "Jane");
+    assert_eq!(firsts.get_index(2).index(), None);
+    assert!(!friends.get_index(usize::MAX).exists());
+    assert!(!get(EXAMPLE, "name").get_index(0).exists());
+    assert!(!get(EXAMPLE, "age").get_index(0).exists());
+}
+
+#[test]
+fn parse_scalars() {
+    for digit in 0..10 {
+        let json = format!(" {}.5 ", digit);
+        assert!(parse(&json).kind() == Kind::Number);
+        assert_eq!(parse(&json).f64(), digit as f64 + 0.5);
+        assert_eq!(parse(&json).json(), format!("{}.5", digit));
+    }
+    assert_eq!(parse(" 3.14 ").f64(), 3.14);
+    assert_eq!(parse("\t\n-2e3,").f64(), -2000.0);
+    assert_eq!(parse("-0").json(), "-0");
+    assert_eq!(parse(r#"  "hi\tthere" x"#).str(), "hi\tthere");
+    assert_eq!(parse(r#""""#).json(), r#""""#);
+    assert!(parse("true ").bool());
+    assert_eq!(parse("true false").json(), "true");
+    assert_eq!(parse("false]").json(), "false");
+    assert_eq!(parse(" null").json(), "null");
+    assert!(parse("null").exists());
+    assert_eq!(parse("12 34").i32(), 12);
+    assert!(!parse("").exists());
+    assert!(!parse("   ").exists());
+    assert!(!parse("x").exists());
+    assert!(!parse("+1").exists());
+    assert!(!parse(".5").exists());
+}
+
+#[test]
+fn coerce_f64() {
+    let json = r#"{"a":"1,234.56","b":"1_000","c":"$5.00","d":"n/a","e":12.5,"f":true,"g":"","h":null}"#;
+    assert_eq!(get(json, "a").coerce_f64(), Some(1234.56));
+    assert_eq!(get(json, "a").f64(), 0.0);
+    assert_eq!(get(json, "b").coerce_f64(), Some(1000.0));
+    assert_eq!(get(json, "c").coerce_f64(), Some(5.0));
+    assert_eq!(get(json, "d").coerce_f64(), None);
+    assert_eq!(get(json, "e").coerce_f64(), Some(12.5));
+    assert_eq!(get(json, "f").coerce_f64(), None);
+    assert_eq!(get(json, "g").coerce_f64(), None);
+    assert_eq!(get(json, "h").coerce_f64(), None);
+    assert_eq!(get(json, "missing").coerce_f64(), None);
+}
+
+#[test]
+fn missing_modifiers() {
+    let json = r#"{"a":null,"b":2,"c":[null,null,3,4],"d":[null]}"#;
+    assert!(!get(json, "missing.@ugly").exists());
+    assert!(!get(json, "missing.@reverse").exists());
+    assert!(!get(json, "missing|@keys").exists());
+    assert!(!get(json, "missing|values").exists());
+    assert!(!get(json, "missing.@this").exists());
+    assert_eq!(get(json, "missing.@root.b").i32(), 2);
+    assert_eq!(get(json, "missing.@default:1").json(), "1");
+    assert_eq!(get(json, "missing|@default:1").json(), "1");
+    assert_eq!(get(json, r#"a.@default:"none""#).str(), "none");
+    assert_eq!(get(json, "b.@default:1").json(), "2");
+    assert_eq!(get(json, r#"missing.@default:{"x":[1]}.x.0"#).i32(), 1);
+    assert!(!get(json, "missing.@default").exists());
+    assert_eq!(get(json, "c.@coalesce").json(), "3");
+    assert_eq!(get(json, "d.@coalesce:0").json(), "0");
+    assert!(!get(json, "d.@coalesce").exists());
+    assert_eq!(get(json, "missing.@coalesce:5").json(), "5");
+    assert_eq!(get(json, "b.@coalesce:5").json(), "2");
+    assert_eq!(get(json, "[a,b].@coalesce").json(), "2");
+    assert_eq!(get(json, "[a,missing].@coalesce:7").json(), "7");
+}
+
+#[test]
+fn enumerate_entries() {
+    let name = get(EXAMPLE, "name");
+    let entries: Vec<(usize, String, String)> = name
+        .enumerate_entries()
+        .map(|(i, key, value)| (i, key.str().to_owned(), value.str().to_owned()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (0, "first".to_owned(), "Tom".to_owned()),
+            (1, "last".to_owned(), "Anderson".to_owned())
+        ]
+    );
+    let root = parse(EXAMPLE);
+    let keys: Vec<(usize, String)> = root
+        .enumerate_entries()
+        .map(|(i, key, _)| (i, key.str().to_owned()))
+        .collect();
+    assert_eq!(keys.len(), 5);
+    assert_eq!(keys[3], (3, "fav.movie".to_owned()));
+    let children = get(EXAMPLE, "children");
+    for (i, key, value) in children.enumerate_entries() {
+        assert!(!key.exists());
+        assert!(value == children.get_index(i));
+    }
+    assert_eq!(children.enumerate_entries().count(), 3);
+    let friends = get(EXAMPLE, "friends");
+    let mut entries = friends.enumerate_entries().skip(1);
+    let (i, _, value) = entries.next().unwrap();
+    assert_eq!((i, value.get("first").str()), (1, "Roger"));
+    assert_eq!(parse("{}").enumerate_entries().count(), 0);
+    assert_eq!(parse("[ ]").enumerate_entries().count(), 0);
+    let age = get(EXAMPLE, "age");
+    let scalar: Vec<_> = age.enumerate_entries().collect();
+    assert_eq!(scalar.len(), 1);
+    assert_eq!((scalar[0].0, scalar[0].2.i32()), (0, 37));
+    assert_eq!(get(EXAMPLE, "missing").enumerate_entries().count(), 0);
+    let json = r#"{"a" : [1, {"b":2}] , "c":"\"x\""}"#;
+    let value = parse(json);
+    let mut count = 0;
+    for (i, key, value) in value.enumerate_entries() {
+        let mut each_i = 0;
+        parse(json).each(|k, v| {
+            if each_i == i {
+                assert_eq!(k.str(), key.str());
+                assert_eq!(v.json(), value.json());
+            }
+            each_i += 1;
+            true
+        });
+        count += 1;
+    }
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn lines_with_comments() {
+    let json = r#"
+# exported users
+{"name": "Gilbert", "age": 61}
+
+  # {"name": "Nobody", "age": 1}
+{"name": "Alexa", "age": 34}
+
+
+{"name": "May", "age": 57}
+#{"name": "Nobody", "age": 2}
+{"name": "Deloise", "age": 44}
+"#;
+    assert_eq!(get_lines_with_comments(json, "..#").i32(), 4);
+    assert_eq!(
+        get_lines_with_comments(json, "..#.name").json(),
+        r#"["Gilbert","Alexa","May","Deloise"]"#
+    );
+    assert_eq!(get_lines_with_comments(json, r#"..#(name="May").age"#).i32(), 57);
+    let value = get_lines_with_comments(json, "..1");
+    assert_eq!(value.json(), r#"{"name": "Alexa", "age": 34}"#);
+    assert_eq!(&json[value.index().unwrap()..][..value.json().len()], value.json());
+    assert_eq!(get_lines_with_comments(json, "..@reverse|0.name").str(), "Deloise");
+    // blank lines are skipped without comments enabled, but comments are not
+    assert_eq!(get("\n\n{\"a\":1}\n\n\n{\"a\":2}\n\n", "..#").i32(), 2);
+    assert_eq!(get("\n\n{\"a\":1}\n\n\n{\"a\":2}\n\n", "..#.a").json(), "[1,2]");
+    assert_ne!(get(json, "..#.name").json(), r#"["Gilbert","Alexa","May","Deloise"]"#);
+    // only the lines are read with comments
+    assert_eq!(get_lines_with_comments(EXAMPLE, "name.first").str(), "Tom");
+    assert_eq!(get_lines_with_comments("[1]\n# [2]\n[3]", "..1|#").i32(), 1);
+}
+
+#[test]
+fn get_all() {
+    let value = parse(r#"{"a":1,"b":{"c":2},"a":3,"b":{"c":4},"ab":5}"#);
+    let all = value.get_all("a");
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].i32(), 1);
+    assert_eq!(all[1].i32(), 3);
+    assert_eq!(value.get("a").i32(), 1);
+    let all = value.get_all("b.c");
+    assert_eq!(all.iter().map(|v| v.i32()).collect::<Vec<_>>(), vec![2, 4]);
+    let all = value.get_all("a*");
+    assert_eq!(all.iter().map(|v| v.i32()).collect::<Vec<_>>(), vec![1, 3, 5]);
+    let all = value.get_all("*");
+    assert_eq!(all.len(), 5);
+    assert_eq!(value.get_all("b.c|@this").len(), 2);
+    assert!(value.get_all("missing").is_empty());
+    assert!(value.get_all("a.b").is_empty());
+    let value = get(EXAMPLE, "@this");
+    let all = value.get_all("friends.1.*");
+    assert_eq!(
+        all.iter().map(|v| v.json()).collect::<Vec<_>>(),
+        vec![r#""Roger""#, r#""Craig""#, "68", r#"["fb", "tw"]"#]
+    );
+    for v in &all {
+        assert_eq!(&EXAMPLE[v.index().unwrap()..][..v.json().len()], v.json());
+    }
+    let all = value.get_all("children.1");
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].str(), "Alex");
+    let all = value.get_all("name.*");
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[1].str(), "Anderson");
+    assert!(value.get_all("friends.*.first").is_empty());
+    let all = value.get_all("friends.*#.first");
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].json(), r#"["Dale","Roger","Jane"]"#);
+    let all = value.get_all("friends.#(age>45)#.last");
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].json(), r#"["Craig","Murphy"]"#);
+    assert_eq!(value.get_all("fav\\.movie")[0].str(), "Deer Hunter");
+}
+
+#[test]
+fn approx_eq() {
+    let a = parse("0.30000000000000004");
+    let b = parse("0.3");
+    assert!(a != b);
+    assert!(a.approx_eq(&b, 1e-9));
+    assert!(b.approx_eq(&a, 1e-9));
+    assert!(!parse("1.5").approx_eq(&parse("1.6"), 0.05));
+    assert!(parse("1.5").approx_eq(&parse("1.6"), 0.15));
+    assert!(parse("-2").approx_eq(&parse("-2.0"), 0.0));
+    assert!(get(EXAMPLE, "age").approx_eq(&parse("37.01"), 0.1));
+    // other kinds are compared exactly
+    assert!(parse(r#""1.0""#).approx_eq(&parse(r#""1.0""#), 1.0));
+    assert!(!parse(r#""1.0""#).approx_eq(&parse(r#""1.1""#), 1.0));
+    assert!(!parse(r#""1""#).approx_eq(&parse("1"), 1.0));
+    assert!(parse("true").approx_eq(&parse("true"), 0.0));
+    assert!(!parse("null").approx_eq(&parse("false"), 1.0));
+}
+
+#[test]
+fn get_or_literal() {
+    let value = parse(EXAMPLE);
+    assert_eq!(value.get_or_literal("age", 99).i64(), 37);
+    assert_eq!(value.get_or_literal("name.middle", "n/a").str(), "n/a");
+    assert_eq!(value.get_or_literal("name.middle", "n/a").json(), r#""n/a""#);
+    assert_eq!(value.get_or_literal("weight", 42).json(), "42");
+    assert!(value.get_or_literal("weight", 42).kind() == Kind::Number);
+    assert_eq!(value.get_or_literal("weight", -1.5).f64(), -1.5);
+    assert_eq!(value.get_or_literal("weight", 0.1f32).json(), "0.1");
+    assert_eq!(value.get_or_literal("weight", f64::NAN).json(), "null");
+    assert_eq!(value.get_or_literal("active", true).json(), "true");
+    assert_eq!(value.get_or_literal("quote", String::from("say \"hi\"")).str(), "say \"hi\"");
+    assert_eq!(value.get_or_literal("quote", "say \"hi\"").json(), r#""say \"hi\"""#);
+    let fallback = parse(r#"["x"]"#);
+    assert_eq!(value.get_or_literal("list", fallback).get("0").str(), "x");
+    // unlike get_or, a string default is not parsed as json
+    assert_eq!(value.get_or("count", "12").i64(), 12);
+    assert!(value.get_or_literal("count", "12").kind() == Kind::String);
+}
+
+#[test]
+fn modifier_then_index() {
+    let json = r#"{"arr":[3,1,2],"statuses":[{"user":{"id":1}},{"user":{"id":2}}]}"#;
+    assert_eq!(get(json, "arr.@reverse|0").i32(), 2);
+    assert_eq!(get(json, "arr.@reverse.0").i32(), 2);
+    assert_eq!(get(json, "arr|@reverse|0").i32(), 2);
+    assert_eq!(get(json, "arr|@reverse.2").i32(), 3);
+    assert_eq!(get(json, "arr.@reverse|@reverse|0").i32(), 3);
+    assert_eq!(get(json, "arr.@reverse|#").i32(), 3);
+    assert!(!get(json, "arr.@reverse|3").exists());
+    assert_eq!(get(json, "statuses.#.user.id|@reverse|0").i32(), 2);
+    assert_eq!(get(json, "statuses.@reverse|0.user.id").i32(), 2);
+    let value = parse(json);
+    let reversed = value.get("arr.@reverse");
+    assert_eq!(reversed.get("0").i32(), 2);
+    assert_eq!(reversed.get("1").i32(), 1);
+    // a modifier output is a new document, so its values have no position
+    // in the original json unless the output is unchanged.
+    assert_eq!(get(json, "arr.@reverse").index(), None);
+    assert_eq!(get(json, "arr.@reverse|0").index(), None);
+    assert_eq!(reversed.get("0").index(), None);
+    assert_eq!(get(json, "arr|length").index(), None);
+    for path in ["arr.@this|0", "arr.@ugly.1", "arr.@valid|2", "statuses.1.@this.user"] {
+        let value = get(json, path);
+        let index = value.index().unwrap();
+        assert_eq!(&json[index..][..value.json().len()], value.json());
+    }
+}
+
+#[test]
+fn heap_size() {
+    // a borrowed value reports its raw length
+    let value = get(EXAMPLE, "children");
+    assert_eq!(value.heap_size(), value.json().len());
+    let reversed = get(EXAMPLE, "children.@reverse");
+    assert_eq!(reversed.json(), r#"["Jack","Alex","Sara"]"#);
+    assert!(reversed.heap_size() >= reversed.json().len());
+    // an escaped string also owns its unescaped copy
+    let escaped = get(r#"{"a":"line\nbreak"}"#, "a");
+    assert!(escaped.heap_size() >= escaped.json().len() + "line\nbreak".len());
+    assert!(escaped.heap_size() > escaped.json().len());
+    // an owned copy also owns the raw key and value
+    let owned = value.detach();
+    let member = value.with_key_raw().unwrap();
+    assert!(owned.heap_size() >= value.json().len() + member.len());
+    assert!(owned.heap_size() > value.heap_size());
+    assert!(escaped.detach().heap_size() > escaped.heap_size());
+    assert_eq!(owned.borrow().heap_size(), value.json().len());
+    assert_eq!(Value::default().heap_size(), 0);
+}
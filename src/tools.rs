@@ -5,5 +5,1164 @@
 // Bit flags passed to the "info" parameter of the iter function which
 // provides additional information about the data
 
-pub use super::pretty::{pretty, ugly, PrettyOptions};
+pub use super::pretty::{pretty, pretty_string, ugly, ugly_preserve_lines, PrettyOptions};
 pub use super::util::{escape, unescape};
+
+use super::path::push_path_key;
+use super::util::{extend_json_string, tostr};
+use super::valid::{trailing, valid_any};
+use super::{parse, scan_number, Kind, Value};
+
+/// Returns a skeleton of the json document describing the type of each value.
+///
+/// ```
+/// let json = r#"{"name":{"first":"Tom"},"age":37,"children":["Sara","Alex"]}"#;
+/// let schema = gjson::tools::infer_schema(json);
+/// assert_eq!(schema, r#"{"name":{"first":"string"},"age":"number","children":["string"]}"#);
+/// ```
+///
+/// Arrays are described using their first element, and an empty array is
+/// returned as `[]`. Booleans are described as "boolean".
+pub fn infer_schema(json: &str) -> String {
+    let mut out = String::new();
+    extend_schema(&mut out, json);
+    out
+}
+
+fn extend_schema(out: &mut String, json: &str) {
+    let value = parse(json);
+    match value.kind() {
+        Kind::Object => {
+            out.push('{');
+            let mut idx = 0;
+            value.each(|key, value| {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push_str(key.json());
+                out.push(':');
+                extend_schema(out, value.json());
+                idx += 1;
+                true
+            });
+            out.push('}');
+        }
+        Kind::Array => {
+            out.push('[');
+            value.each(|_, value| {
+                extend_schema(out, value.json());
+                false
+            });
+            out.push(']');
+        }
+        Kind::True | Kind::False => out.push_str(r#""boolean""#),
+        kind => {
+            if value.exists() {
+                out.push('"');
+                out.push_str(kind.name());
+                out.push('"');
+            }
+        }
+    }
+}
+
+/// Splits a buffer of back-to-back json values, such as `{"a":1}{"a":2}[3]`,
+/// into a slice for each value.
+///
+/// Values may be separated by whitespace. A number, `true`, `false`, or `null`
+/// must be followed by whitespace, the end of the buffer, or the start of an
+/// object, array, or string, which means that `4 true` splits into two values
+/// while `4true` is invalid.
+/// Splitting stops at the first invalid value.
+pub fn split_values(json: &str) -> Vec<&str> {
+    let json = json.as_bytes();
+    let mut values = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < json.len() && json[i] <= b' ' {
+            i += 1;
+        }
+        if i == json.len() {
+            break;
+        }
+        let (valid, next_i) = valid_any(json, i, usize::MAX);
+        if !valid {
+            break;
+        }
+        if json[next_i - 1].is_ascii_alphanumeric()
+            && next_i < json.len()
+            && json[next_i] > b' '
+            && !matches!(json[next_i], b'{' | b'[' | b'"')
+        {
+            break;
+        }
+        values.push(tostr(&json[i..next_i]));
+        i = next_i;
+    }
+    values
+}
+
+/// Returns the byte offset up to which the buffer is a sequence of complete
+/// json values, such that `&json[..offset]` leaves out a trailing value that
+/// was cut off, like the end of a partially received stream.
+///
+/// Values are separated the same as `split_values`. A number that runs to the
+/// end of the buffer is not complete, because more digits may follow.
+///
+/// ```
+/// let json = r#"{"a":1} {"a":2} {"a"#;
+/// let offset = gjson::tools::last_complete_offset(json);
+/// assert_eq!(&json[..offset], r#"{"a":1} {"a":2}"#);
+/// ```
+pub fn last_complete_offset(json: &str) -> usize {
+    let json = json.as_bytes();
+    let mut offset = 0;
+    let mut i = 0;
+    loop {
+        while i < json.len() && json[i] <= b' ' {
+            i += 1;
+        }
+        if i == json.len() {
+            break;
+        }
+        let (valid, next_i) = valid_any(json, i, usize::MAX);
+        if !valid || (next_i == json.len() && matches!(json[i], b'-' | b'0'..=b'9')) {
+            break;
+        }
+        if json[next_i - 1].is_ascii_alphanumeric()
+            && next_i < json.len()
+            && json[next_i] > b' '
+            && !matches!(json[next_i], b'{' | b'[' | b'"')
+        {
+            break;
+        }
+        offset = next_i;
+        i = next_i;
+    }
+    offset
+}
+
+/// Parses the json as a single document, returning the value only when the
+/// entire input, ignoring surrounding whitespace, is exactly one valid json
+/// value.
+///
+/// Unlike `parse`, which ignores anything that follows the first value,
+/// trailing content is an error. The error is the offset of the trailing
+/// content, or the offset where validation failed for invalid json.
+///
+/// ```
+/// assert_eq!(gjson::tools::parse_single(r#"{"a":1}"#).unwrap().get("a").i32(), 1);
+/// assert_eq!(gjson::tools::parse_single(r#"{"a":1} junk"#).err(), Some(8));
+/// ```
+pub fn parse_single(json: &str) -> Result<Value<'_>, usize> {
+    let (valid, i) = valid_any(json.as_bytes(), 0, usize::MAX);
+    if !valid {
+        return Err(i);
+    }
+    match trailing(json.as_bytes(), i) {
+        Some(i) => Err(i),
+        None => Ok(parse(&json[..i])),
+    }
+}
+
+/// Returns the path and raw json of every scalar value in the document, in
+/// document order, such as `("friends.0.last", "\"Murphy\"")`.
+///
+/// Array elements use their index as the path component, and special
+/// characters in keys are escaped so that each path can be passed to `get`.
+/// Empty objects and arrays have no scalar values and are skipped, and a
+/// document that is a single scalar returns one pair with an empty path.
+pub fn leaf_paths(json: &str) -> Vec<(String, String)> {
+    let mut leaves = Vec::new();
+    extend_leaf_paths(&mut leaves, &mut String::new(), json);
+    leaves
+}
+
+fn extend_leaf_paths(leaves: &mut Vec<(String, String)>, path: &mut String, json: &str) {
+    let value = parse(json);
+    let kind = value.kind();
+    if kind != Kind::Object && kind != Kind::Array {
+        if value.exists() {
+            leaves.push((path.clone(), value.json().to_owned()));
+        }
+        return;
+    }
+    let mut index = 0;
+    value.each(|key, value| {
+        let mark = path.len();
+        if mark > 0 {
+            path.push('.');
+        }
+        if kind == Kind::Object {
+            push_path_key(path, key.str());
+        } else {
+            path.push_str(&index.to_string());
+        }
+        extend_leaf_paths(leaves, path, value.json());
+        path.truncate(mark);
+        index += 1;
+        true
+    });
+}
+
+/// Joins keys and indexes into a path, escaping any characters that have a
+/// special meaning in a path, such as `.`, `*`, `?`, `#`, `|`, `@`, and `\`.
+///
+/// ```
+/// let path = gjson::tools::join_path(&["fav.movie", "0"]);
+/// assert_eq!(path, "fav\\.movie.0");
+/// ```
+pub fn join_path(components: &[&str]) -> String {
+    let mut path = String::new();
+    for (i, comp) in components.iter().enumerate() {
+        if i > 0 {
+            path.push('.');
+        }
+        push_path_key(&mut path, comp);
+    }
+    path
+}
+
+/// Returns the key as a quoted json string, with any quotes, backslashes,
+/// and control characters escaped, which is always a valid object member name.
+///
+/// ```
+/// let key = gjson::tools::escape_key("say \"hi\"");
+/// let json = format!("{{{}:1}}", key);
+/// assert_eq!(json, r#"{"say \"hi\"":1}"#);
+/// ```
+///
+/// This is the same as `escape`. To use the key in a path, rather than in
+/// json, see `join_path`.
+pub fn escape_key(key: &str) -> String {
+    let mut out = Vec::with_capacity(key.len() + 2);
+    extend_json_string(&mut out, key.as_bytes());
+    // SAFETY: the key is utf8 and only ascii escapes were added.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+/// Converts json that has bare identifier keys, such as `{name: "Tom"}`, to
+/// standard json by wrapping those keys in quotes.
+///
+/// ```
+/// let json = gjson::tools::json5_to_json(r#"{name: {first: "Tom"}, "age": 37}"#);
+/// assert_eq!(json, br#"{"name": {"first": "Tom"}, "age": 37}"#);
+/// let json = std::str::from_utf8(&json).unwrap();
+/// assert_eq!(gjson::get(json, "name.first").str(), "Tom");
+/// ```
+///
+/// Only keys that look like identifiers, `[A-Za-z_$][A-Za-z0-9_$]*`, are
+/// quoted. Everything else, including the contents of strings, is copied
+/// as-is.
+pub fn json5_to_json(json: &str) -> Vec<u8> {
+    let json = json.as_bytes();
+    let mut out = Vec::with_capacity(json.len());
+    // the last non-whitespace character that was written
+    let mut last = 0;
+    let mut i = 0;
+    while i < json.len() {
+        let c = json[i];
+        if c == b'"' {
+            let s = i;
+            i += 1;
+            while i < json.len() {
+                if json[i] == b'\\' {
+                    i += 1;
+                } else if json[i] == b'"' {
+                    i += 1;
+                    break;
+                }
+                i += 1;
+            }
+            let e = i.min(json.len());
+            out.extend(&json[s..e]);
+            last = b'"';
+            continue;
+        }
+        if (last == b'{' || last == b',') && (c.is_ascii_alphabetic() || c == b'_' || c == b'$') {
+            let s = i;
+            while i < json.len()
+                && (json[i].is_ascii_alphanumeric() || json[i] == b'_' || json[i] == b'$')
+            {
+                i += 1;
+            }
+            let mut j = i;
+            while j < json.len() && json[j] <= b' ' {
+                j += 1;
+            }
+            if j < json.len() && json[j] == b':' {
+                out.push(b'"');
+                out.extend(&json[s..i]);
+                out.push(b'"');
+            } else {
+                out.extend(&json[s..i]);
+            }
+            last = json[i - 1];
+            continue;
+        }
+        out.push(c);
+        if c > b' ' {
+            last = c;
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Converts single-quoted strings and keys, such as `{'name':'Tom'}`, to
+/// double-quoted json strings.
+///
+/// ```
+/// let json = gjson::tools::single_to_double_quotes(r#"{'msg':'say "hi"'}"#);
+/// assert_eq!(json, br#"{"msg":"say \"hi\""}"#);
+/// ```
+///
+/// Double quotes inside of a single-quoted string are escaped, and `\'`
+/// becomes a plain `'`. Double-quoted strings, including any apostrophes
+/// inside of them, are copied as-is.
+pub fn single_to_double_quotes(json: &str) -> Vec<u8> {
+    let json = json.as_bytes();
+    let mut out = Vec::with_capacity(json.len());
+    let mut i = 0;
+    while i < json.len() {
+        match json[i] {
+            b'"' => {
+                let s = i;
+                i += 1;
+                while i < json.len() {
+                    if json[i] == b'\\' {
+                        i += 1;
+                    } else if json[i] == b'"' {
+                        break;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(json.len());
+                out.extend(&json[s..i]);
+            }
+            b'\'' => {
+                out.push(b'"');
+                i += 1;
+                while i < json.len() {
+                    match json[i] {
+                        b'\'' => {
+                            out.push(b'"');
+                            break;
+                        }
+                        b'"' => out.extend(b"\\\""),
+                        b'\\' if i + 1 < json.len() && json[i + 1] == b'\'' => {
+                            out.push(b'\'');
+                            i += 1;
+                        }
+                        b'\\' if i + 1 < json.len() => {
+                            out.extend(&json[i..i + 2]);
+                            i += 1;
+                        }
+                        c => out.push(c),
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Returns the byte offset of every object key, at any depth, that is named
+/// key. The offset is the position of the key's opening quote.
+///
+/// Strings that are values, rather than keys, are never matched, even when
+/// their contents are the same as the key.
+///
+/// ```
+/// let json = r#"{"id":1,"name":"id","child":{"id":2}}"#;
+/// assert_eq!(gjson::tools::find_key(json, "id"), [1, 29]);
+/// ```
+pub fn find_key(json: &str, key: &str) -> Vec<usize> {
+    let json = json.as_bytes();
+    let mut offsets = Vec::new();
+    let mut i = 0;
+    while i < json.len() {
+        if json[i] != b'"' {
+            i += 1;
+            continue;
+        }
+        let s = i;
+        let mut esc = false;
+        i += 1;
+        while i < json.len() {
+            if json[i] == b'\\' {
+                esc = true;
+                i += 1;
+            } else if json[i] == b'"' {
+                break;
+            }
+            i += 1;
+        }
+        if i >= json.len() {
+            break;
+        }
+        i += 1;
+        let raw = &json[s..i];
+        let mut j = i;
+        while j < json.len() && json[j] <= b' ' {
+            j += 1;
+        }
+        if j == json.len() || json[j] != b':' {
+            // not a key
+            continue;
+        }
+        let is_match = if esc {
+            unescape(tostr(raw)) == key
+        } else {
+            &raw[1..raw.len() - 1] == key.as_bytes()
+        };
+        if is_match {
+            offsets.push(s);
+        }
+    }
+    offsets
+}
+
+/// Converts every number in the json to a string containing the exact text
+/// of the number, such that `{"id":123456789012345678}` becomes
+/// `{"id":"123456789012345678"}`.
+///
+/// This allows for numbers to be read without losing precision, because
+/// nothing is ever converted to a float.
+pub fn numbers_to_strings(json: &str) -> Vec<u8> {
+    let json = json.as_bytes();
+    let mut out = Vec::with_capacity(json.len());
+    let mut i = 0;
+    while i < json.len() {
+        match json[i] {
+            b'"' => {
+                let s = i;
+                i += 1;
+                while i < json.len() {
+                    if json[i] == b'\\' {
+                        i += 1;
+                    } else if json[i] == b'"' {
+                        break;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(json.len());
+                out.extend(&json[s..i]);
+            }
+            b'-' | b'0'..=b'9' => {
+                let (num, _, next_i) = scan_number(json, i);
+                out.push(b'"');
+                out.extend(num);
+                out.push(b'"');
+                i = next_i;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Returns how deeply arrays are nested directly in arrays, such as `3` for
+/// `[1,[2,[3]]]`, which is the depth that a deep `@flatten` would descend.
+///
+/// A single array is `1`, and any other value is `0`. Arrays inside of
+/// objects are not counted, because `@flatten` does not flatten them.
+///
+/// ```
+/// assert_eq!(gjson::tools::max_array_depth("[1,[2,[3]]]"), 3);
+/// assert_eq!(gjson::tools::max_array_depth(r#"[{"a":[[1]]}]"#), 1);
+/// ```
+pub fn max_array_depth(json: &str) -> usize {
+    let value = parse(json);
+    if value.kind() != Kind::Array {
+        return 0;
+    }
+    let mut depth = 0;
+    value.each(|_, value| {
+        depth = depth.max(max_array_depth(value.json()));
+        true
+    });
+    depth + 1
+}
+
+/// Returns the json in a pretty format for human reading, where any string
+/// that runs past `width` columns is wrapped onto indented continuation
+/// lines.
+///
+/// This is for display only. A wrapped string contains raw line breaks, so
+/// the output is not valid json and must not be parsed. Use `pretty` for
+/// output that needs to round trip.
+///
+/// ```
+/// let json = r#"{"quote":"the quick brown fox jumps over the lazy dog"}"#;
+/// println!("{}", gjson::tools::pretty_display(json, 24));
+/// ```
+pub fn pretty_display(json: &str, width: usize) -> String {
+    let pretty = pretty(json);
+    let mut out = String::with_capacity(pretty.len());
+    for line in pretty.lines() {
+        let lead = &line[..line.len() - line.trim_start().len()];
+        let wrap = lead.chars().count() + 2;
+        let mut col = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in line.chars() {
+            if in_string && col >= width && col > wrap {
+                out.push('\n');
+                out.push_str(lead);
+                out.push_str("  ");
+                col = wrap;
+            }
+            out.push(c);
+            col += 1;
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = in_string;
+            } else if c == '"' {
+                in_string = !in_string;
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Returns the raw json of every value of the kind anywhere in the document,
+/// including the document itself, in document order.
+///
+/// Object keys are not values, so they are never returned as strings.
+///
+/// ```
+/// use gjson::Kind;
+/// let json = r#"{"a":[1,{"b":2.5}],"c":"3"}"#;
+/// assert_eq!(gjson::tools::all_of_kind(json, Kind::Number), ["1", "2.5"]);
+/// ```
+pub fn all_of_kind(json: &str, kind: Kind) -> Vec<String> {
+    let mut all = Vec::new();
+    parse(json).walk(|_, value| {
+        if value.kind() == kind {
+            all.push(value.json().to_owned());
+        }
+    });
+    all
+}
+
+/// Returns the value as yaml, where objects become mappings, arrays become
+/// sequences, and strings are written plainly unless they need quotes.
+///
+/// ```
+/// let value = gjson::parse(r#"{"name":"Tom","tags":["a","b"],"age":37}"#);
+/// assert_eq!(gjson::tools::to_yaml(&value), "name: Tom\ntags:\n  - a\n  - b\nage: 37\n");
+/// ```
+///
+/// Only the common subset of yaml is produced, without anchors, tags, or
+/// block strings. A quoted string is written as a json string, which yaml
+/// reads as a double-quoted string. Nothing is returned for a value that
+/// does not exist.
+pub fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    if value.exists() {
+        extend_yaml(&mut out, value, 0);
+    }
+    out
+}
+
+// extend_yaml writes the value starting at the current column, with each of
+// its following lines indented by indent spaces.
+fn extend_yaml(out: &mut String, value: &Value, indent: usize) {
+    let kind = value.kind();
+    if !yaml_nested(value) {
+        extend_yaml_scalar(out, value);
+        out.push('\n');
+        return;
+    }
+    let mut first = true;
+    value.each(|key, value| {
+        if !first {
+            out.push_str(&" ".repeat(indent));
+        }
+        first = false;
+        if kind == Kind::Array {
+            out.push_str("- ");
+            extend_yaml(out, &value, indent + 2);
+        } else {
+            extend_yaml_scalar(out, &key);
+            out.push(':');
+            if yaml_nested(&value) {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent + 2));
+                extend_yaml(out, &value, indent + 2);
+            } else {
+                out.push(' ');
+                extend_yaml(out, &value, indent);
+            }
+        }
+        true
+    });
+}
+
+// yaml_nested returns true if the value is an object or array that has
+// children, which are written on their own lines. Empty objects and arrays
+// are written inline as `{}` and `[]`.
+fn yaml_nested(value: &Value) -> bool {
+    let mut nested = false;
+    if value.is_container() {
+        value.each(|_, _| {
+            nested = true;
+            false
+        });
+    }
+    nested
+}
+
+fn extend_yaml_scalar(out: &mut String, value: &Value) {
+    match value.kind() {
+        Kind::String => {
+            let s = value.str();
+            if yaml_plain(s) {
+                out.push_str(s);
+            } else {
+                out.push_str(&escape(s));
+            }
+        }
+        Kind::Object => out.push_str("{}"),
+        Kind::Array => out.push_str("[]"),
+        _ => out.push_str(value.json()),
+    }
+}
+
+// yaml_plain returns true if the string can be written without quotes and
+// still be read back as the same string.
+fn yaml_plain(s: &str) -> bool {
+    let first = match s.chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    if first.is_ascii_digit()
+        || first.is_whitespace()
+        || "-+.?:,[]{}#&*!|>'\"%@`~".contains(first)
+        || s.ends_with(char::is_whitespace)
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.chars().any(char::is_control)
+    {
+        return false;
+    }
+    let reserved = [
+        "true", "false", "null", "yes", "no", "on", "off", "y", "n", ".inf", ".nan",
+    ];
+    !reserved.iter().any(|word| s.eq_ignore_ascii_case(word))
+}
+
+/// Counts of the values in a json document, returned from `stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// The number of keys in all objects.
+    pub keys: usize,
+    /// The deepest nesting of objects and arrays, which is zero for a
+    /// document that is a single scalar value.
+    pub max_depth: usize,
+}
+
+/// Returns counts of each kind of value in the json document, without
+/// parsing it into values.
+///
+/// ```
+/// let stats = gjson::tools::stats(r#"{"a":[1,2,{"b":null}],"c":"d"}"#);
+/// assert_eq!(stats.numbers, 2);
+/// assert_eq!(stats.keys, 3);
+/// assert_eq!(stats.max_depth, 3);
+/// ```
+///
+/// This function expects that the json is valid, and does not validate.
+pub fn stats(json: &str) -> Stats {
+    let json = json.as_bytes();
+    let mut stats = Stats::default();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < json.len() {
+        match json[i] {
+            b'{' | b'[' => {
+                if json[i] == b'{' {
+                    stats.objects += 1;
+                } else {
+                    stats.arrays += 1;
+                }
+                depth += 1;
+                stats.max_depth = stats.max_depth.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                i += 1;
+                while i < json.len() {
+                    if json[i] == b'\\' {
+                        i += 1;
+                    } else if json[i] == b'"' {
+                        break;
+                    }
+                    i += 1;
+                }
+                let mut j = i + 1;
+                while j < json.len() && json[j] <= b' ' {
+                    j += 1;
+                }
+                if j < json.len() && json[j] == b':' {
+                    stats.keys += 1;
+                } else {
+                    stats.strings += 1;
+                }
+            }
+            b'-' | b'0'..=b'9' => {
+                stats.numbers += 1;
+                i = scan_number(json, i).2;
+                continue;
+            }
+            b't' | b'f' => {
+                stats.booleans += 1;
+                i += if json[i] == b't' { 4 } else { 5 };
+                continue;
+            }
+            b'n' => {
+                stats.nulls += 1;
+                i += 4;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::EXAMPLE;
+
+    #[test]
+    fn infer_schema() {
+        assert_eq!(
+            super::infer_schema(EXAMPLE),
+            r#"{"name":{"first":"string","last":"string"},"age":"number","children":["string"],"fav.movie":"string","friends":[{"first":"string","last":"string","age":"number","nets":["string"]}]}"#
+        );
+        assert_eq!(
+            super::infer_schema(r#"{"active":true,"spouse":null,"pets":[]}"#),
+            r#"{"active":"boolean","spouse":"null","pets":[]}"#
+        );
+        assert_eq!(super::infer_schema("12"), r#""number""#);
+        assert_eq!(super::infer_schema(""), "");
+    }
+
+    #[test]
+    fn split_values() {
+        assert_eq!(
+            super::split_values(r#"{"a":1}{"a":2}[3]"#),
+            [r#"{"a":1}"#, r#"{"a":2}"#, "[3]"]
+        );
+        assert_eq!(
+            super::split_values(" [1, 2]\n\t[ ]  {} \n"),
+            ["[1, 2]", "[ ]", "{}"]
+        );
+        assert_eq!(
+            super::split_values(r#"4 true false null -1.5e3 "x""y"[1]"#),
+            ["4", "true", "false", "null", "-1.5e3", r#""x""#, r#""y""#, "[1]"]
+        );
+        assert_eq!(super::split_values(r#"4"x"7[1]"#), ["4", r#""x""#, "7", "[1]"]);
+        assert_eq!(super::split_values("1 4true"), ["1"]);
+        assert_eq!(super::split_values("[1] {"), ["[1]"]);
+        assert_eq!(super::split_values("nul"), Vec::<&str>::new());
+        assert_eq!(super::split_values("  "), Vec::<&str>::new());
+        for value in super::split_values(r#"{"a":1}{"a":2}"#) {
+            assert!(crate::get(value, "a").exists());
+        }
+    }
+
+    #[test]
+    fn leaf_paths() {
+        let leaves = super::leaf_paths(EXAMPLE);
+        assert_eq!(leaves.len(), 23);
+        assert_eq!(leaves[0], ("name.first".to_owned(), r#""Tom""#.to_owned()));
+        assert_eq!(leaves[2], ("age".to_owned(), "37".to_owned()));
+        assert_eq!(leaves[4], ("children.1".to_owned(), r#""Alex""#.to_owned()));
+        assert_eq!(leaves[6], ("fav\\.movie".to_owned(), r#""Deer Hunter""#.to_owned()));
+        assert!(leaves.contains(&("friends.0.last".to_owned(), r#""Murphy""#.to_owned())));
+        assert_eq!(leaves[22], ("friends.2.nets.1".to_owned(), r#""tw""#.to_owned()));
+        for (path, value) in &leaves {
+            assert_eq!(crate::get(EXAMPLE, path).json(), value);
+        }
+        assert_eq!(
+            super::leaf_paths(r#"{"empty":{},"none":null}"#),
+            [("none".to_owned(), "null".to_owned())]
+        );
+        assert_eq!(super::leaf_paths("12"), [("".to_owned(), "12".to_owned())]);
+        assert!(super::leaf_paths("[]").is_empty());
+        assert!(super::leaf_paths("").is_empty());
+    }
+
+    #[test]
+    fn join_path() {
+        let keys = [
+            "a.b", "a*", "a?", "#", "a|b", "@this", "a\\b", "%", "a b", "a:b-c_d", "(x)",
+            "[y]", "{z}", "!=", "é", "\"q\"", "",
+        ];
+        let mut json = Vec::new();
+        json.push(b'{');
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                json.push(b',');
+            }
+            crate::util::extend_json_string(&mut json, key.as_bytes());
+            json.extend(format!(r#":{{"v":[{}]}}"#, i).as_bytes());
+        }
+        json.push(b'}');
+        let json = String::from_utf8(json).unwrap();
+        assert!(crate::valid(&json));
+        for (i, key) in keys.iter().enumerate() {
+            let path = super::join_path(&[key, "v", "0"]);
+            assert_eq!(crate::get(&json, &path).i32(), i as i32, "{}", path);
+        }
+        assert_eq!(super::join_path(&["a.b", "c"]), "a\\.b.c");
+        assert_eq!(super::join_path(&["friends", "1", "first"]), "friends.1.first");
+        assert_eq!(super::join_path(&[]), "");
+    }
+
+    #[test]
+    fn escape_key() {
+        assert_eq!(super::escape_key("name"), r#""name""#);
+        assert_eq!(super::escape_key(r#"a"b"#), r#""a\"b""#);
+        assert_eq!(super::escape_key("a\\b"), r#""a\\b""#);
+        assert_eq!(super::escape_key("\n\t\r\u{1}\u{8}\u{c}"), r#""\n\t\r\u0001\b\f""#);
+        assert_eq!(super::escape_key(""), r#""""#);
+        for key in &[r#"say "hi""#, "tab\there", "\u{0}\u{1f}", "é\"\\", "a.b*"] {
+            let json = format!("{{{}:1}}", super::escape_key(key));
+            assert!(crate::valid(&json), "{}", json);
+            let mut found = false;
+            crate::parse(&json).each(|k, _| {
+                found = k.str() == *key;
+                true
+            });
+            assert!(found, "{}", json);
+            let path = super::join_path(&[key]);
+            assert_eq!(crate::get(&json, &path).i32(), 1);
+        }
+    }
+
+    #[test]
+    fn json5_to_json() {
+        assert_eq!(super::json5_to_json(r#"{name:"Tom"}"#), br#"{"name":"Tom"}"#);
+        assert_eq!(
+            super::json5_to_json("{ _id : 1,\n\t$ref2: [true, null, 1e5], ok: false }"),
+            b"{ \"_id\" : 1,\n\t\"$ref2\": [true, null, 1e5], \"ok\": false }"
+        );
+        assert_eq!(
+            super::json5_to_json(r#"{"a": {b: "x: y, c: z"}, "d": "\"e: f"}"#),
+            br#"{"a": {"b": "x: y, c: z"}, "d": "\"e: f"}"#
+        );
+        assert_eq!(super::json5_to_json(r#"[{a:1},{b:2}]"#), br#"[{"a":1},{"b":2}]"#);
+        assert_eq!(super::json5_to_json(r#"{"a":[true,false]}"#), br#"{"a":[true,false]}"#);
+        assert_eq!(super::json5_to_json("{1a:1,é:2}"), "{1a:1,é:2}".as_bytes());
+        assert_eq!(super::json5_to_json(r#"{a:"unterminated"#), br#"{"a":"unterminated"#);
+        let json = super::json5_to_json(r#"{users: [{name: "Tom", "age": 37}]}"#);
+        let json = std::str::from_utf8(&json).unwrap();
+        assert!(crate::valid(json));
+        assert_eq!(crate::get(json, "users.0.age").i32(), 37);
+    }
+
+    #[test]
+    fn single_to_double_quotes() {
+        assert_eq!(
+            super::single_to_double_quotes("{'name':'Tom','age':37}"),
+            br#"{"name":"Tom","age":37}"#
+        );
+        assert_eq!(
+            super::single_to_double_quotes(r#"{'msg':'say "hi"','it':'it\'s'}"#),
+            br#"{"msg":"say \"hi\"","it":"it's"}"#
+        );
+        assert_eq!(
+            super::single_to_double_quotes(r#"{"a":"it's","b":['x\ny',"'"]}"#),
+            br#"{"a":"it's","b":["x\ny","'"]}"#
+        );
+        assert_eq!(super::single_to_double_quotes("['open"), br#"["open"#);
+        let json = super::single_to_double_quotes(r#"{'users':[{'name':'a "b" c'}]}"#);
+        let json = std::str::from_utf8(&json).unwrap();
+        assert!(crate::valid(json));
+        assert_eq!(crate::get(json, "users.0.name").str(), r#"a "b" c"#);
+    }
+
+    #[test]
+    fn max_array_depth() {
+        assert_eq!(super::max_array_depth("[1,[2,[3]]]"), 3);
+        assert_eq!(super::max_array_depth("[]"), 1);
+        assert_eq!(super::max_array_depth("[[],[[[]]],[1]]"), 4);
+        assert_eq!(super::max_array_depth(r#"[{"a":[[1]]},["x"]]"#), 2);
+        assert_eq!(super::max_array_depth(r#"{"a":[[1]]}"#), 0);
+        assert_eq!(super::max_array_depth("1"), 0);
+        assert_eq!(super::max_array_depth(""), 0);
+        let json = "[1,[2],[3,4],[5,[6,7]]]";
+        assert_eq!(super::max_array_depth(json), 3);
+        let flat = crate::get(json, "@flatten");
+        assert_eq!(super::max_array_depth(flat.json()), 2);
+        let flat = crate::get(json, r#"@flatten:{"deep":true}"#);
+        assert_eq!(super::max_array_depth(flat.json()), 1);
+    }
+
+    #[test]
+    fn find_key() {
+        let json = r#"{"id":1,"name":"id","tags":["id","x"],"child":{"id" : 2, "note":"\"id\":3"}}"#;
+        let offsets = super::find_key(json, "id");
+        assert_eq!(offsets.len(), 2);
+        for offset in offsets {
+            assert_eq!(&json[offset..offset + 4], r#""id""#);
+        }
+        assert_eq!(super::find_key(json, "id")[0], 1);
+        assert_eq!(super::find_key(r#"{"\u0069d":1}"#, "id"), [1]);
+        assert_eq!(super::find_key(r#"{"a\"b":1,"a":2}"#, "a\"b"), [1]);
+        assert!(super::find_key(json, "missing").is_empty());
+        assert!(super::find_key(r#"{"id"#, "id").is_empty());
+    }
+
+    #[test]
+    fn numbers_to_strings() {
+        assert_eq!(
+            super::numbers_to_strings(r#"{"id":123456789012345678}"#),
+            br#"{"id":"123456789012345678"}"#
+        );
+        assert_eq!(
+            super::numbers_to_strings(r#"[1, -2.50, 3e+10, 0.1E-2, "4", true, null, {"5":6}]"#),
+            br#"["1", "-2.50", "3e+10", "0.1E-2", "4", true, null, {"5":"6"}]"#
+        );
+        assert_eq!(
+            super::numbers_to_strings(r#"{"a\"1":"b\\","c":10.10}"#),
+            br#"{"a\"1":"b\\","c":"10.10"}"#
+        );
+        assert_eq!(super::numbers_to_strings("7"), br#""7""#);
+        assert_eq!(super::numbers_to_strings(r#"["1"#), br#"["1"#);
+        let json = super::numbers_to_strings(r#"{"price":19.990000000000001}"#);
+        let json = std::str::from_utf8(&json).unwrap();
+        assert_eq!(crate::get(json, "price").str(), "19.990000000000001");
+    }
+
+    #[test]
+    fn stats() {
+        let stats = super::stats(EXAMPLE);
+        assert_eq!(
+            stats,
+            super::Stats {
+                objects: 5,
+                arrays: 5,
+                strings: 19,
+                numbers: 4,
+                booleans: 0,
+                nulls: 0,
+                keys: 19,
+                max_depth: 4,
+            }
+        );
+        let stats = super::stats(r#"[true, false, null, -1.5e3, "a\":", {"b\"" : [[]]}]"#);
+        assert_eq!(stats.booleans, 2);
+        assert_eq!(stats.nulls, 1);
+        assert_eq!(stats.numbers, 1);
+        assert_eq!(stats.strings, 1);
+        assert_eq!(stats.keys, 1);
+        assert_eq!(stats.arrays, 3);
+        assert_eq!(stats.objects, 1);
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(super::stats("12").max_depth, 0);
+        assert_eq!(super::stats(""), super::Stats::default());
+    }
+
+    #[test]
+    fn last_complete_offset() {
+        // mid-object
+        let json = "{\"a\":1}\n{\"a\":2}\n{\"a\":{\"b\":";
+        let offset = super::last_complete_offset(json);
+        assert_eq!(offset, 15);
+        assert_eq!(crate::get(&json[..offset], "..#.a").json(), "[1,2]");
+        // mid-string
+        let json = r#"[1,2] "done" "not do"#;
+        assert_eq!(&json[..super::last_complete_offset(json)], r#"[1,2] "done""#);
+        let json = r#"{"a":"b\"#;
+        assert_eq!(super::last_complete_offset(json), 0);
+        // mid-number and mid-literal
+        assert_eq!(super::last_complete_offset("[1] 12"), 3);
+        assert_eq!(super::last_complete_offset("[1] 12 "), 6);
+        assert_eq!(super::last_complete_offset("[1] tr"), 3);
+        assert_eq!(super::last_complete_offset("[1] true"), 8);
+        // complete buffers
+        let json = r#"{"a":1}{"b":2}[3]"#;
+        assert_eq!(super::last_complete_offset(json), json.len());
+        assert_eq!(super::last_complete_offset("  {}  "), 4);
+        assert_eq!(super::last_complete_offset(""), 0);
+        // an invalid value stops at the values before it
+        assert_eq!(super::last_complete_offset("[1] {]"), 3);
+    }
+
+    #[test]
+    fn to_yaml() {
+        let value = crate::parse(EXAMPLE);
+        let expect = "\
+name:
+  first: Tom
+  last: Anderson
+age: 37
+children:
+  - Sara
+  - Alex
+  - Jack
+fav.movie: Deer Hunter
+friends:
+  - first: Dale
+    last: Murphy
+    age: 44
+    nets:
+      - ig
+      - fb
+      - tw
+  - first: Roger
+    last: Craig
+    age: 68
+    nets:
+      - fb
+      - tw
+  - first: Jane
+    last: Murphy
+    age: 47
+    nets:
+      - ig
+      - tw
+";
+        assert_eq!(super::to_yaml(&value), expect);
+        assert_eq!(super::to_yaml(&value.get("name.first")), "Tom\n");
+        assert_eq!(super::to_yaml(&value.get("age")), "37\n");
+        assert_eq!(super::to_yaml(&value.get("missing")), "");
+        let value = crate::parse(
+            r#"{
+              "matrix": [[1, 2], [3]],
+              "quoted": ["", "true", "12", "- x", "a: b", "line\nbreak", " pad", "No"],
+              "nets": [],
+              "empty": {},
+              "spouse": null,
+              "active": true
+            }"#,
+        );
+        let expect = "\
+matrix:
+  - - 1
+    - 2
+  - - 3
+quoted:
+  - \"\"
+  - \"true\"
+  - \"12\"
+  - \"- x\"
+  - \"a: b\"
+  - \"line\\nbreak\"
+  - \" pad\"
+  - \"No\"
+nets: []
+empty: {}
+spouse: null
+active: true
+";
+        assert_eq!(super::to_yaml(&value), expect);
+        assert_eq!(super::to_yaml(&crate::parse("[]")), "[]\n");
+    }
+
+    #[test]
+    fn parse_single() {
+        let value = super::parse_single(r#"{"a":1}"#).unwrap();
+        assert_eq!(value.get("a").i32(), 1);
+        assert_eq!(super::parse_single("  [1, 2]\n").unwrap().json(), "[1, 2]");
+        assert_eq!(super::parse_single(r#""hi""#).unwrap().str(), "hi");
+        assert_eq!(super::parse_single("12").unwrap().i32(), 12);
+        assert_eq!(super::parse_single(r#"{"a":1} junk"#).err(), Some(8));
+        assert_eq!(super::parse_single(r#"{"a":1}{"a":2}"#).err(), Some(7));
+        assert_eq!(super::parse_single("1 2").err(), Some(2));
+        assert_eq!(super::parse_single("truex").err(), Some(4));
+        assert!(super::parse_single(r#"{"a":}"#).is_err());
+        assert!(super::parse_single("").is_err());
+        assert!(super::parse_single("  ").is_err());
+    }
+
+    #[test]
+    fn all_of_kind() {
+        use crate::Kind;
+        const JSON: &str = r#"
+        {
+          "name": {"first": "Tom", "last": "Anderson"},
+          "age":37,
+          "children": ["Sara","Alex","Jack"],
+          "fav.movie": "Deer \"Hunter\"",
+          "friends": [
+            {"first": "Dale", "last": "Murphy", "age": 44, "nets": ["ig", "fb", "tw"]},
+            {"first": "Roger", "last": "Craig", "age": 68, "nets": ["fb", "tw"]}
+          ]
+        }
+        "#;
+        assert_eq!(
+            super::all_of_kind(JSON, Kind::String),
+            [
+                r#""Tom""#,
+                r#""Anderson""#,
+                r#""Sara""#,
+                r#""Alex""#,
+                r#""Jack""#,
+                r#""Deer \"Hunter\"""#,
+                r#""Dale""#,
+                r#""Murphy""#,
+                r#""ig""#,
+                r#""fb""#,
+                r#""tw""#,
+                r#""Roger""#,
+                r#""Craig""#,
+                r#""fb""#,
+                r#""tw""#,
+            ]
+        );
+        assert_eq!(super::all_of_kind(JSON, Kind::Number), ["37", "44", "68"]);
+        assert_eq!(super::all_of_kind(JSON, Kind::Object).len(), 4);
+        assert_eq!(super::all_of_kind(JSON, Kind::Array)[0], r#"["Sara","Alex","Jack"]"#);
+        assert!(super::all_of_kind(JSON, Kind::Null).is_empty());
+        assert_eq!(super::all_of_kind("true", Kind::True), ["true"]);
+        assert!(super::all_of_kind("", Kind::String).is_empty());
+    }
+
+    #[test]
+    fn pretty_display() {
+        let long = "abcdefghij".repeat(5);
+        let json = format!(r#"{{"a":{{"text":"{}","n":1}}}}"#, long);
+        let display = super::pretty_display(&json, 30);
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines[0], "{");
+        assert_eq!(lines[2], r#"    "text": "abcdefghijabcdefg"#);
+        assert_eq!(lines[3], "      hijabcdefghijabcdefghija");
+        assert_eq!(lines[4], r#"      bcdefghij","#);
+        assert!(lines.iter().all(|line| line.chars().count() <= 30));
+        // the wrapped text is the same once the continuations are removed
+        let joined: String = lines[2..5].iter().map(|line| line.trim_start()).collect();
+        assert_eq!(joined, format!(r#""text": "{}","#, long));
+        assert!(!crate::valid(&display));
+        // nothing is wrapped when the strings fit
+        let json = r#"{"a":"b","c":[1,2,3]}"#;
+        assert_eq!(super::pretty_display(json, 80), super::pretty(json));
+        // escapes don't end a string and multibyte characters take one column
+        let display = super::pretty_display(r#"["\"ééééééééé\""]"#, 10);
+        assert_eq!(display, "[\"\\\"éééééé\n  ééé\\\"\"]\n");
+    }
+}
@@ -160,6 +160,140 @@ pub fn escape(s: &str) -> String {
     unsafe { std::mem::transmute::<Vec<u8>, String>(out) }
 }
 
+// fmt_usize formats a count as a plain json integer, without separators or
+// exponents, regardless of size.
+pub fn fmt_usize(mut n: usize) -> String {
+    let mut buf = [0u8; 20];
+    let mut i = buf.len();
+    loop {
+        i -= 1;
+        buf[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    tostr(&buf[i..]).to_owned()
+}
+
+// exceeds_depth returns true if the objects and arrays in the json, or the
+// queries and multipaths in a path, are nested deeper than max_depth.
+// The scan is iterative, making it safe for any input.
+pub fn exceeds_depth(json: &[u8], max_depth: usize) -> bool {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < json.len() {
+        match json[i] {
+            b'{' | b'[' | b'(' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b'}' | b']' | b')' => depth = depth.saturating_sub(1),
+            b'\\' => i += 1,
+            b'"' => {
+                i += 1;
+                while i < json.len() && json[i] != b'"' {
+                    if json[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+// leading_f64 parses the number at the start of a string, which is an
+// optional sign followed by digits, ignoring everything following the number.
+// For example, "44kg" returns 44, while "v2" and "$5.00" are not numbers.
+pub fn leading_f64(s: &str) -> Option<f64> {
+    let s = s.as_bytes();
+    let mut i = 0;
+    if i < s.len() && (s[i] == b'-' || s[i] == b'+') {
+        i += 1;
+    }
+    if i == s.len() || !s[i].is_ascii_digit() {
+        return None;
+    }
+    while i < s.len() && s[i].is_ascii_digit() {
+        i += 1;
+    }
+    if i + 1 < s.len() && s[i] == b'.' && s[i + 1].is_ascii_digit() {
+        i += 1;
+        while i < s.len() && s[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+    if i + 1 < s.len() && (s[i] == b'e' || s[i] == b'E') {
+        let mut j = i + 1;
+        if j + 1 < s.len() && (s[j] == b'-' || s[j] == b'+') {
+            j += 1;
+        }
+        if s[j].is_ascii_digit() {
+            i = j;
+            while i < s.len() && s[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+    }
+    tostr(&s[..i]).parse().ok()
+}
+
+// sanitized_f64 parses a number after removing whitespace, the thousands
+// separators ',' and '_', and the currency symbols '$', '€', '£', and '¥'.
+// For example, "1,234.56" returns 1234.56 and "$5.00" returns 5. Unlike
+// leading_f64, anything else in the string makes it unparseable.
+pub fn sanitized_f64(s: &str) -> Option<f64> {
+    let mut num = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ',' | '_' | '$' | '€' | '£' | '¥' => {}
+            c if c.is_whitespace() => {}
+            '0'..='9' | '.' | '-' | '+' | 'e' | 'E' => num.push(c),
+            _ => return None,
+        }
+    }
+    if !num.bytes().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    num.parse().ok()
+}
+
+// percent_decode decodes each `%XX` hex escape in s, such that "first%20name"
+// returns "first name". A '%' that isn't followed by two hex digits is kept
+// as is, and decoded bytes that aren't valid utf8 are replaced with U+FFFD.
+pub fn percent_decode(s: &str) -> String {
+    let s = s.as_bytes();
+    let mut out = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] == b'%' && i + 2 < s.len() {
+            if let (Some(hi), Some(lo)) = (hexval(s[i + 1]), hexval(s[i + 2])) {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(s[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hexval(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
 /// pmatch returns true if str matches pattern. This is a very
 /// simple wildcard match where '*' matches on any number characters
 /// and '?' matches on any one character.
@@ -176,41 +310,103 @@ where
     S: AsRef<[u8]>,
     P: AsRef<[u8]>,
 {
-    let mut string = string.as_ref();
-    let mut pattern = pattern.as_ref();
-    while pattern.len() > 0 {
-        if pattern[0] == b'\\' {
-            if pattern.len() == 1 {
-                return false;
-            }
-            pattern = &pattern[1..];
-        } else if pattern[0] == b'*' {
-            if pattern.len() == 1 {
-                return true;
-            }
-            if pattern[1] == b'*' {
-                pattern = &pattern[1..];
+    let string = string.as_ref();
+    let pattern = pattern.as_ref();
+    let (mut p, mut s) = (0, 0);
+    // The positions just after the last '*' and the string position that it
+    // is currently matched up to. On a mismatch the '*' is extended by one
+    // character, so each '*' is only ever backtracked to once.
+    let mut star = None;
+    while s < string.len() {
+        if p < pattern.len() {
+            let (is_match, n) = match pattern[p] {
+                b'*' => {
+                    p += 1;
+                    star = Some((p, s));
+                    continue;
+                }
+                b'?' => (true, 1),
+                b'\\' => (p + 1 < pattern.len() && pattern[p + 1] == string[s], 2),
+                c => (c == string[s], 1),
+            };
+            if is_match {
+                p += n;
+                s += 1;
                 continue;
             }
-            if pmatch(&pattern[1..], string) {
-                return true;
-            }
-            if string.len() == 0 {
-                return false;
+        }
+        match star {
+            Some((sp, ss)) => {
+                p = sp;
+                s = ss + 1;
+                star = Some((sp, s));
             }
-            string = &string[1..];
-            continue;
+            None => return false,
         }
-        if string.len() == 0 {
-            return false;
+    }
+    while p < pattern.len() && pattern[p] == b'*' {
+        p += 1;
+    }
+    p == pattern.len()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// base64_encode encodes the bytes using the standard base64 alphabet, with
+// padding.
+pub fn base64_encode(src: &[u8]) -> String {
+    let mut out = Vec::with_capacity(src.len().div_ceil(3) * 4);
+    for chunk in src.chunks(3) {
+        let mut n = 0;
+        for j in 0..3 {
+            n = n << 8 | *chunk.get(j).unwrap_or(&0) as u32;
         }
-        if pattern[0] != b'?' && string[0] != pattern[0] {
-            return false;
+        for j in 0..4 {
+            if j <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * j)) as usize & 63]);
+            } else {
+                out.push(b'=');
+            }
         }
-        pattern = &pattern[1..];
-        string = &string[1..];
     }
-    return string.len() == 0 && pattern.len() == 0;
+    // SAFETY: the base64 alphabet is ascii
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+// base64_decode decodes standard or url-safe base64, with or without padding.
+// Returns None when the input is not valid base64.
+pub fn base64_decode(src: &str) -> Option<Vec<u8>> {
+    let src = src.as_bytes();
+    let pad = src.iter().rev().take_while(|c| **c == b'=').count();
+    let partial = src.len() % 4;
+    if pad > 2 || (pad > 0 && partial > 0) {
+        return None;
+    }
+    let data = &src[..src.len() - pad];
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &c in data {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
 }
 
 #[cfg(test)]
@@ -237,12 +433,86 @@ mod test {
         assert_eq!(true, super::pmatch("hello\\?", "hello?",));
         assert_eq!(true, super::pmatch("hello\\\\", "hello\\",));
 
+        assert!(!super::pmatch("hello\\?", "hellox"));
+        assert!(!super::pmatch("h\\*llo", "hello"));
+        assert!(super::pmatch("*world", "helloworld"));
+        assert!(super::pmatch("*world", "worldworld"));
+        assert!(!super::pmatch("*world", "worldworlds"));
+        assert!(super::pmatch("hello*world", "helloworldworld"));
+        assert!(super::pmatch("hello*world", "helloworld"));
+        assert!(!super::pmatch("hello*world", "hellworld"));
+        assert!(super::pmatch("a*b*c", "axbyc"));
+        assert!(super::pmatch("a*b*c", "aXbXbc"));
+        assert!(super::pmatch("a*b*c", "abc"));
+        assert!(!super::pmatch("a*b*c", "aXbXbcX"));
+        assert!(super::pmatch("a**?", "ab"));
+        assert!(!super::pmatch("a**?", "a"));
+        assert!(super::pmatch("*?*", "x"));
+        assert!(super::pmatch("a*\\*", "ab*"));
+        assert!(!super::pmatch("a*\\*", "ab"));
+
+        // many stars must not backtrack exponentially
+        let string = "a".repeat(100);
+        assert!(!super::pmatch("a*a*a*a*a*a*a*a*a*a*a*a*b", &string));
+
         // test for fast repeating stars
         let string = ",**,,**,**,**,**,**,**,";
         let pattern = ",**********************************************{**\",**,,**,**,**,**,\"\",**,**,**,**,**,**,**,**,**,**]";
         super::pmatch(pattern, string);
     }
     #[test]
+    fn fmt_usize() {
+        assert_eq!(super::fmt_usize(0), "0");
+        assert_eq!(super::fmt_usize(7), "7");
+        assert_eq!(super::fmt_usize(1234567), "1234567");
+        assert_eq!(super::fmt_usize(1_000_000_000_000), "1000000000000");
+        assert_eq!(super::fmt_usize(usize::MAX), usize::MAX.to_string());
+    }
+    #[test]
+    fn leading_f64() {
+        assert_eq!(super::leading_f64("44kg"), Some(44.0));
+        assert_eq!(super::leading_f64("5.00 USD"), Some(5.0));
+        assert_eq!(super::leading_f64("-1.5e2m"), Some(-150.0));
+        assert_eq!(super::leading_f64("+3.5.3"), Some(3.5));
+        assert_eq!(super::leading_f64("12e"), Some(12.0));
+        assert_eq!(super::leading_f64("12."), Some(12.0));
+        // only a sign may come before the digits
+        assert_eq!(super::leading_f64("$5.00"), None);
+        assert_eq!(super::leading_f64("v2"), None);
+        assert_eq!(super::leading_f64("abc-1"), None);
+        assert_eq!(super::leading_f64(".5"), None);
+        assert_eq!(super::leading_f64("-"), None);
+        assert_eq!(super::leading_f64("kg"), None);
+        assert_eq!(super::leading_f64(""), None);
+    }
+    #[test]
+    fn sanitized_f64() {
+        assert_eq!(super::sanitized_f64("1,234.56"), Some(1234.56));
+        assert_eq!(super::sanitized_f64("1_000"), Some(1000.0));
+        assert_eq!(super::sanitized_f64("$5.00"), Some(5.0));
+        assert_eq!(super::sanitized_f64(" -€1 000,5 "), Some(-10005.0));
+        assert_eq!(super::sanitized_f64("£2e3"), Some(2000.0));
+        assert_eq!(super::sanitized_f64("12"), Some(12.0));
+        assert_eq!(super::sanitized_f64("44kg"), None);
+        assert_eq!(super::sanitized_f64("hello"), None);
+        assert_eq!(super::sanitized_f64("inf"), None);
+        assert_eq!(super::sanitized_f64("e"), None);
+        assert_eq!(super::sanitized_f64("1.2.3"), None);
+        assert_eq!(super::sanitized_f64("$"), None);
+        assert_eq!(super::sanitized_f64(""), None);
+    }
+    #[test]
+    fn percent_decode() {
+        assert_eq!(super::percent_decode("first%20name"), "first name");
+        assert_eq!(super::percent_decode("a%2Eb%2eC"), "a.b.C");
+        assert_eq!(super::percent_decode("%E2%82%AC1"), "€1");
+        assert_eq!(super::percent_decode("100%"), "100%");
+        assert_eq!(super::percent_decode("%4"), "%4");
+        assert_eq!(super::percent_decode("%zz%41"), "%zzA");
+        assert_eq!(super::percent_decode("%FF"), "\u{FFFD}");
+        assert_eq!(super::percent_decode(""), "");
+    }
+    #[test]
     fn escape() {
         let text = r#"
 第一印象:なんか怖っ！
@@ -278,4 +548,34 @@ LINE交換できる？:あぁ……ごめん✋
         assert_eq!(super::unescape(r#""ad\uD83D\uxxxxsf""#), "ad�sf");
         assert_eq!(super::unescape(r#""ad\uD83D\u00FFsf""#), "ad�sf");
     }
+
+    #[test]
+    fn base64() {
+        let cases = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in cases {
+            assert_eq!(super::base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(super::base64_decode(encoded).unwrap(), plain.as_bytes());
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(super::base64_decode(unpadded).unwrap(), plain.as_bytes());
+        }
+        let bytes: Vec<u8> = (0..=255).collect();
+        let encoded = super::base64_encode(&bytes);
+        assert_eq!(super::base64_decode(&encoded).unwrap(), bytes);
+        let url_safe = encoded.replace('+', "-").replace('/', "_");
+        assert_eq!(super::base64_decode(&url_safe).unwrap(), bytes);
+        assert_eq!(super::base64_decode("Zg"), Some(b"f".to_vec()));
+        assert_eq!(super::base64_decode("Zg="), None);
+        assert_eq!(super::base64_decode("Zg==="), None);
+        assert_eq!(super::base64_decode("Z"), None);
+        assert_eq!(super::base64_decode("Zm9v!"), None);
+        assert_eq!(super::base64_decode("Zm 9v"), None);
+    }
 }
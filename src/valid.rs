@@ -53,6 +53,9 @@ static TABLE: [u8; 256] = {
     table
 };
 
+use super::util::{tostr, unescape};
+use std::collections::HashSet;
+
 fn isspace(c: u8) -> bool {
     TABLE[c as usize] & SPACE == SPACE
 }
@@ -66,31 +69,93 @@ fn isspace(c: u8) -> bool {
 /// let value = gjson::get(json, "name.last");
 /// ```
 pub fn valid(json: &str) -> bool {
+    valid_with_depth(json, usize::MAX)
+}
+
+/// Returns true if the input is valid json and objects and arrays are not
+/// nested deeper than max_depth.
+///
+/// This should be preferred over `valid` for untrusted input, because very
+/// deeply nested json may otherwise overflow the stack.
+///
+/// ```
+/// assert!(gjson::valid_with_depth("[[1]]", 2));
+/// assert!(!gjson::valid_with_depth("[[[1]]]", 2));
+/// ```
+pub fn valid_with_depth(json: &str, max_depth: usize) -> bool {
     let json = json.as_bytes();
-    let mut i = 0;
-    let (valid, next_i) = valid_any(json, i);
+    let (valid, i) = valid_any(json, 0, max_depth);
+    valid && trailing(json, i).is_none()
+}
+
+/// Returns an error if the input is not valid json, or if any object has
+/// the same key more than once.
+///
+/// Duplicate keys are allowed by RFC 8259, and by `valid`, so this is only
+/// useful for inputs that must be stricter. The error is the offset of the
+/// first duplicate key's opening quote and the key, or the offset where
+/// validation failed and an empty key for invalid json.
+///
+/// ```
+/// assert_eq!(gjson::valid_no_dupes(r#"{"a":{"b":1,"c":2}}"#), Ok(()));
+/// assert_eq!(gjson::valid_no_dupes(r#"{"a":1,"a":2}"#), Err((7, "a".to_owned())));
+/// ```
+pub fn valid_no_dupes(json: &str) -> Result<(), (usize, String)> {
+    let json = json.as_bytes();
+    let mut dupes = Dupes {
+        check: true,
+        found: None,
+    };
+    let (valid, i) = valid_value(json, 0, usize::MAX, &mut dupes);
+    if let Some(found) = dupes.found {
+        return Err(found);
+    }
     if !valid {
-        return false;
+        return Err((i, String::new()));
     }
-    i = next_i;
+    match trailing(json, i) {
+        Some(i) => Err((i, String::new())),
+        None => Ok(()),
+    }
+}
+
+// trailing returns the offset of the first byte at or after i that is not
+// whitespace, which is content that follows a complete json value.
+pub fn trailing(json: &[u8], mut i: usize) -> Option<usize> {
     while i < json.len() {
         if !isspace(json[i]) {
-            return false;
+            return Some(i);
         }
         i += 1;
     }
-    true
+    None
+}
+
+// Dupes tracks the first duplicate object key, when check is set.
+struct Dupes {
+    check: bool,
+    found: Option<(usize, String)>,
+}
+
+// The depth param is the remaining number of objects and arrays that may be
+// nested.
+pub fn valid_any(json: &[u8], i: usize, depth: usize) -> (bool, usize) {
+    let mut dupes = Dupes {
+        check: false,
+        found: None,
+    };
+    valid_value(json, i, depth, &mut dupes)
 }
 
-fn valid_any(json: &[u8], mut i: usize) -> (bool, usize) {
+fn valid_value(json: &[u8], mut i: usize, depth: usize, dupes: &mut Dupes) -> (bool, usize) {
     while i < json.len() {
         if isspace(json[i]) {
             i += 1;
             continue;
         }
         return match json[i] {
-            b'{' => valid_object(json, i),
-            b'[' => valid_array(json, i),
+            b'{' if depth > 0 => valid_object(json, i, depth - 1, dupes),
+            b'[' if depth > 0 => valid_array(json, i, depth - 1, dupes),
             b'"' => valid_string(json, i),
             b't' => valid_true(json, i),
             b'f' => valid_false(json, i),
@@ -127,7 +192,7 @@ fn strip_ws(json: &[u8], mut i: usize) -> usize {
     }
 }
 
-fn valid_object(json: &[u8], mut i: usize) -> (bool, usize) {
+fn valid_object(json: &[u8], mut i: usize, depth: usize, dupes: &mut Dupes) -> (bool, usize) {
     i = strip_ws(json, i + 1);
     if i == json.len() {
         return (false, i);
@@ -135,6 +200,7 @@ fn valid_object(json: &[u8], mut i: usize) -> (bool, usize) {
     if json[i] == b'}' {
         return (true, i + 1);
     }
+    let mut keys = HashSet::new();
     loop {
         if json[i] != b'"' {
             return (false, i);
@@ -143,6 +209,14 @@ fn valid_object(json: &[u8], mut i: usize) -> (bool, usize) {
         if !valid {
             return (false, i);
         }
+        if dupes.check {
+            let key = unescape(tostr(&json[i..next_i]));
+            if keys.contains(&key) {
+                dupes.found = Some((i, key));
+                return (false, i);
+            }
+            keys.insert(key);
+        }
         i = next_i;
         i = strip_ws(json, i);
         if i == json.len() {
@@ -151,7 +225,7 @@ fn valid_object(json: &[u8], mut i: usize) -> (bool, usize) {
         if json[i] != b':' {
             return (false, i);
         }
-        let (valid, next_i) = valid_any(json, i + 1);
+        let (valid, next_i) = valid_value(json, i + 1, depth, dupes);
         if !valid {
             return (false, i);
         }
@@ -173,7 +247,7 @@ fn valid_object(json: &[u8], mut i: usize) -> (bool, usize) {
     }
 }
 
-fn valid_array(json: &[u8], mut i: usize) -> (bool, usize) {
+fn valid_array(json: &[u8], mut i: usize, depth: usize, dupes: &mut Dupes) -> (bool, usize) {
     i = strip_ws(json, i + 1);
     if i == json.len() {
         return (false, i);
@@ -182,7 +256,7 @@ fn valid_array(json: &[u8], mut i: usize) -> (bool, usize) {
         return (true, i + 1);
     }
     loop {
-        let (valid, next_i) = valid_any(json, i);
+        let (valid, next_i) = valid_value(json, i, depth, dupes);
         if !valid {
             return (false, i);
         }
@@ -440,6 +514,41 @@ mod test {
         assert_eq!(valid("[-.123]"), false);
     }
 
+    #[test]
+    fn depth() {
+        use super::valid_with_depth;
+        assert!(valid_with_depth("1", 0));
+        assert!(!valid_with_depth("[]", 0));
+        assert!(valid_with_depth("[]", 1));
+        assert!(valid_with_depth(r#"{"a":[1,{"b":2}]}"#, 3));
+        assert!(!valid_with_depth(r#"{"a":[1,{"b":2}]}"#, 2));
+        assert!(valid_with_depth(r#"[[1],[2],{"a":[3]}]"#, 3));
+
+        // deeply nested input must not overflow the stack
+        let n = 1_000_000;
+        let deep = format!("{}{}", "[".repeat(n), "]".repeat(n));
+        assert!(!valid_with_depth(&deep, 500));
+        let deep = format!("{}1{}", r#"{"a":"#.repeat(n), "}".repeat(n));
+        assert!(!valid_with_depth(&deep, 500));
+        let deep = format!("{}{}", "[".repeat(400), "]".repeat(400));
+        assert!(valid_with_depth(&deep, 500));
+    }
+
+    #[test]
+    fn no_dupes() {
+        use super::valid_no_dupes;
+        assert_eq!(valid_no_dupes(r#"{"a":"b","a":1}"#), Err((9, "a".to_owned())));
+        assert_eq!(
+            valid_no_dupes(r#"[{"a":1},{"b":{"c":1,"d":2,"\u0063":3}}]"#),
+            Err((27, "c".to_owned()))
+        );
+        assert_eq!(valid_no_dupes(r#"{"a":{"a":{"a":1}},"b":[{"a":1},{"a":2}]}"#), Ok(()));
+        assert_eq!(valid_no_dupes(r#"{"a":1,"A":2} "#), Ok(()));
+        assert_eq!(valid_no_dupes(r#"[1,2]x"#), Err((5, String::new())));
+        assert_eq!(valid_no_dupes(r#"{"a":1,"#).map_err(|e| e.1), Err(String::new()));
+        assert_eq!(valid_no_dupes("1"), Ok(()));
+    }
+
     #[test]
     fn xcover() {
         // code coverage